@@ -0,0 +1,85 @@
+//! Time averages over one revolution
+use std::f64::consts::{ PI };
+use crate::{ Anomaly, Body, Orbit };
+
+/// Samples per revolution for the averages below, enough to resolve the density spike at
+/// periapsis of an eccentric orbit
+pub(crate) const AVERAGING_SAMPLES : usize = 720;
+
+impl Orbit {
+
+    /// Time average of `f` over one revolution, sampling `samples` anomalies evenly spaced in mean
+    /// anomaly (and so in time) rather than true anomaly, so the slow stretch near apoapsis carries
+    /// its proper weight. Samples sit at midpoints, which for a smooth periodic `f` converges far
+    /// faster than the sample count suggests. The anomalies' `time_ms` count from a periapsis pass at
+    /// zero. NaN for open orbits, which never complete a revolution, or no samples.
+    pub fn orbit_averaged<F : Fn(&Orbit, &Anomaly) -> f64>(&self, body : &Body, f : F, samples : usize) -> f64 {
+        let period = match self.orbital_period(body) {
+            Some(period) if samples > 0 => period,
+            _ => return f64::NAN
+        };
+        let total : f64 = (0..samples)
+            .map(|j| {
+                let fraction = (j as f64 + 0.5) / samples as f64;
+                let anomaly = Anomaly::from_mean(self, 2.0 * PI * fraction, (fraction * period * 1000.0).round() as u64);
+                f(self, &anomaly)
+            })
+            .sum();
+        total / samples as f64
+    }
+
+    /// Time-averaged altitude in meters, following the body's flattening like `altitude`. NaN for open orbits.
+    pub fn mean_altitude(&self, body : &Body) -> f64 {
+        self.orbit_averaged(body, |orbit, anomaly| orbit.altitude(body, anomaly), AVERAGING_SAMPLES)
+    }
+
+    /// Time-averaged density (kg/m^3) of an exponential atmosphere `rho0 · exp(-h / scale_height)`,
+    /// `rho0` being the density at zero altitude and `scale_height` in meters. On an eccentric orbit
+    /// this is dominated by the short pass through periapsis. NaN for open orbits.
+    pub fn mean_density(&self, body : &Body, rho0 : f64, scale_height : f64) -> f64 {
+        self.orbit_averaged(body, |orbit, anomaly| rho0 * (-orbit.altitude(body, anomaly) / scale_height).exp(), AVERAGING_SAMPLES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ earth, Anomaly, Orbit };
+
+    #[test]
+    fn time_average_of_radius() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.4 };
+        let a = orbit.semimajor_axis().unwrap();
+        // <r> over time is a(1 + e²/2), and <1/r> is 1/a
+        let mean_radius = orbit.orbit_averaged(&body, |orbit, anomaly| orbit.distance_from_parent(anomaly), 64);
+        let mean_inverse = orbit.orbit_averaged(&body, |orbit, anomaly| 1.0 / orbit.distance_from_parent(anomaly), 64);
+        assert!((mean_radius / (a * (1.0 + 0.08)) - 1.0).abs() < 1e-9);
+        assert!((mean_inverse * a - 1.0).abs() < 1e-9);
+
+        let hyperbolic = Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 2.0 };
+        assert!(hyperbolic.orbit_averaged(&body, |_, _| 1.0, 64).is_nan());
+    }
+
+    #[test]
+    fn mean_altitude_and_density_weight_by_time() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 6.671e6, eccentricity : 0.02 };
+        // Coarse reference: step in true anomaly and weight each step by the time it takes, r²/h dν
+        let steps = 20_000;
+        let (mut time, mut altitude, mut density) = (0.0, 0.0, 0.0);
+        for j in 0..steps {
+            let nu = 2.0 * std::f64::consts::PI * (j as f64 + 0.5) / steps as f64;
+            let anomaly = Anomaly::from_true(&orbit, nu, 0);
+            let dt = 1.0 / orbit.true_anomaly_rate(&body, &anomaly);
+            let h = orbit.altitude(&body, &anomaly);
+            time += dt;
+            altitude += h * dt;
+            density += 1.2 * (-h / 8500.0).exp() * dt;
+        }
+        assert!((orbit.mean_altitude(&body) / (altitude / time) - 1.0).abs() < 1e-6);
+        assert!((orbit.mean_density(&body, 1.2, 8500.0) / (density / time) - 1.0).abs() < 1e-4);
+        // Naive averaging over true anomaly would give the wrong answer
+        let naive = (0..steps).map(|j| orbit.altitude(&body, &Anomaly::from_true(&orbit, 2.0 * std::f64::consts::PI * j as f64 / steps as f64, 0))).sum::<f64>() / steps as f64;
+        assert!((naive - orbit.mean_altitude(&body)).abs() > 1e3);
+    }
+}
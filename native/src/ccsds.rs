@@ -0,0 +1,288 @@
+//! CCSDS navigation data messages in their keyword = value (KVN) text form
+use std::collections::{ HashMap };
+use std::io::{ self, Write };
+use crate::{ Anomaly, Body, Ephemeris, Orbit, OrbitError };
+
+/// km^3/s^2, Earth's gravitational parameter as assumed by OMMs that omit GM
+pub const OMM_DEFAULT_GM : f64 = 398_600.441_8;
+
+/// Header and metadata common to the messages written here
+pub struct MessageMetadata<'a> {
+    /// Who produced the message
+    pub originator : &'a str,
+    /// milliseconds since the Unix epoch
+    pub creation_ms : u64,
+    pub object_name : &'a str,
+    /// International designator, e.g. "1998-067A"
+    pub object_id : &'a str,
+    /// The central body, e.g. "EARTH"
+    pub center_name : &'a str,
+    /// e.g. "EME2000", "GCRF", "TEME"
+    pub ref_frame : &'a str,
+    /// e.g. "UTC", "TAI", "TDB"
+    pub time_system : &'a str
+}
+
+impl Ephemeris {
+
+    /// Writes the samples as a CCSDS Orbit Ephemeris Message (OEM 2.0) in KVN, one data line per
+    /// sample with position in km and velocity in km/s. The samples are written as they are; the
+    /// frame and time system in `metadata` only label them.
+    pub fn write_ephemeris<W : Write>(&self, writer : &mut W, metadata : &MessageMetadata) -> io::Result<()> {
+        let samples = self.samples();
+        writeln!(writer, "CCSDS_OEM_VERS = 2.0")?;
+        write_header(writer, metadata)?;
+        writeln!(writer)?;
+        writeln!(writer, "META_START")?;
+        write_metadata(writer, metadata)?;
+        if let (Some(first), Some(last)) = (samples.first(), samples.last()) {
+            writeln!(writer, "START_TIME = {}", format_epoch(first.time_ms()))?;
+            writeln!(writer, "STOP_TIME = {}", format_epoch(last.time_ms()))?;
+        }
+        writeln!(writer, "META_STOP")?;
+        writeln!(writer)?;
+        for state in samples {
+            let (p, v) = (state.position() * 1e-3, state.velocity() * 1e-3);
+            writeln!(writer, "{} {:.6} {:.6} {:.6} {:.9} {:.9} {:.9}", format_epoch(state.time_ms()), p.x, p.y, p.z, v.x, v.y, v.z)?;
+        }
+        Ok(())
+    }
+}
+
+impl Orbit {
+
+    /// Reads the mean elements and epoch of a CCSDS Orbit Mean-Elements Message (OMM) in KVN. The
+    /// size may be given as SEMI_MAJOR_AXIS (km) or MEAN_MOTION (rev/day), the latter converted with
+    /// GM if the message has one and `OMM_DEFAULT_GM` otherwise. Angles are degrees. The elements are
+    /// taken at face value: SGP4 mean elements in particular are not osculating, so the orbit this
+    /// gives is only an approximation to what SGP4 would propagate.
+    pub fn from_omm_kvn(text : &str) -> Result<(Orbit, Anomaly), OrbitError> {
+        let fields = parse_kvn(text);
+        let number = |keyword : &str| -> Result<f64, OrbitError> {
+            let value = fields.get(keyword).ok_or_else(|| OrbitError::MissingField(keyword.to_string()))?;
+            value.parse().map_err(|_| invalid(keyword, value))
+        };
+        let gm = if fields.contains_key("GM") { number("GM")? } else { OMM_DEFAULT_GM };
+        let semimajor_axis = if fields.contains_key("SEMI_MAJOR_AXIS") {
+            number("SEMI_MAJOR_AXIS")? * 1e3
+        } else {
+            let mean_motion = number("MEAN_MOTION")? * 2.0 * std::f64::consts::PI / 86_400.0;
+            (gm / (mean_motion * mean_motion)).cbrt() * 1e3
+        };
+        let eccentricity = number("ECCENTRICITY")?;
+        if !(0.0..1.0).contains(&eccentricity) {
+            return Err(invalid("ECCENTRICITY", &fields["ECCENTRICITY"]));
+        }
+        let epoch = fields.get("EPOCH").ok_or_else(|| OrbitError::MissingField("EPOCH".to_string()))?;
+        let time_ms = parse_epoch(epoch).ok_or_else(|| invalid("EPOCH", epoch))?;
+
+        let orbit = Orbit::from_elements(
+            semimajor_axis * (1.0 - eccentricity),
+            eccentricity,
+            number("INCLINATION")?.to_radians(),
+            number("RA_OF_ASC_NODE")?.to_radians(),
+            number("ARG_OF_PERICENTER")?.to_radians());
+        let anomaly = Anomaly::from_mean(&orbit, number("MEAN_ANOMALY")?.to_radians().rem_euclid(2.0 * std::f64::consts::PI), time_ms);
+        Ok((orbit, anomaly))
+    }
+
+    /// Writes this orbit as of `anomaly` as a CCSDS OMM 2.0 in KVN, with the semi-major axis (km),
+    /// angles (degrees), and GM (km^3/s^2) of `body`. `mean_element_theory` labels what the elements
+    /// mean, e.g. "DSST". Open orbits have no OMM form and write an error, as do "SGP4" and
+    /// "SGP4-XP", whose messages need a mean motion and the TLE parameter block (NORAD_CAT_ID,
+    /// BSTAR, ...) that an orbit doesn't carry.
+    pub fn write_omm<W : Write>(&self, writer : &mut W, body : &Body, anomaly : &Anomaly, metadata : &MessageMetadata, mean_element_theory : &str) -> io::Result<()> {
+        if mean_element_theory == "SGP4" || mean_element_theory == "SGP4-XP" {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} OMM elements need the TLE parameter block", mean_element_theory)));
+        }
+        let semimajor_axis = match (self.orbital_period(body), self.semimajor_axis()) {
+            (Some(_), Some(a)) => a,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "OMM elements need a closed orbit"))
+        };
+        writeln!(writer, "CCSDS_OMM_VERS = 2.0")?;
+        write_header(writer, metadata)?;
+        writeln!(writer)?;
+        write_metadata(writer, metadata)?;
+        writeln!(writer, "MEAN_ELEMENT_THEORY = {}", mean_element_theory)?;
+        writeln!(writer)?;
+        writeln!(writer, "EPOCH = {}", format_epoch(anomaly.time_ms))?;
+        writeln!(writer, "SEMI_MAJOR_AXIS = {:.6}", semimajor_axis * 1e-3)?;
+        writeln!(writer, "ECCENTRICITY = {:.8}", self.eccentricity())?;
+        writeln!(writer, "INCLINATION = {:.6}", self.inclination().to_degrees())?;
+        writeln!(writer, "RA_OF_ASC_NODE = {:.6}", self.ascending_node().to_degrees())?;
+        writeln!(writer, "ARG_OF_PERICENTER = {:.6}", self.angle_of_periapsis().to_degrees())?;
+        writeln!(writer, "MEAN_ANOMALY = {:.6}", anomaly.mean_anomaly.to_degrees())?;
+        writeln!(writer, "GM = {:.4}", body.k() * 1e-9)
+    }
+}
+
+pub(crate) fn invalid(keyword : &str, value : &str) -> OrbitError {
+    OrbitError::InvalidValue { keyword : keyword.to_string(), value : value.to_string() }
+}
+
+/// Keyword -> value, dropping comments, block markers, and trailing `[unit]` annotations
+pub(crate) fn parse_kvn(text : &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| {
+            let (keyword, value) = line.split_once('=')?;
+            let keyword = keyword.trim();
+            let value = value.trim();
+            let value = value.split(" [").next().unwrap_or(value).trim();
+            (keyword != "COMMENT").then(|| (keyword.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Milliseconds since the Unix epoch from `YYYY-MM-DDThh:mm:ss[.s...]`, None before 1970 or malformed
+pub(crate) fn parse_epoch(text : &str) -> Option<u64> {
+    let (date, time) = text.split_once('T')?;
+    let mut date = date.split('-').map(|part| part.parse::<i64>());
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.trim_end_matches('Z').split(':');
+    let (hour, minute) = (time.next()?.parse::<u64>().ok()?, time.next()?.parse::<u64>().ok()?);
+    let second = time.next()?.parse::<f64>().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || !(0.0..61.0).contains(&second) {
+        return None;
+    }
+    let days = days_from_civil(year, month as u32, day as u32);
+    if days < 0 {
+        return None;
+    }
+    Some(days as u64 * 86_400_000 + hour * 3_600_000 + minute * 60_000 + (second * 1000.0).round() as u64)
+}
+
+pub(crate) fn write_header<W : Write>(writer : &mut W, metadata : &MessageMetadata) -> io::Result<()> {
+    writeln!(writer, "CREATION_DATE = {}", format_epoch(metadata.creation_ms))?;
+    writeln!(writer, "ORIGINATOR = {}", metadata.originator)
+}
+
+pub(crate) fn write_metadata<W : Write>(writer : &mut W, metadata : &MessageMetadata) -> io::Result<()> {
+    writeln!(writer, "OBJECT_NAME = {}", metadata.object_name)?;
+    writeln!(writer, "OBJECT_ID = {}", metadata.object_id)?;
+    writeln!(writer, "CENTER_NAME = {}", metadata.center_name)?;
+    writeln!(writer, "REF_FRAME = {}", metadata.ref_frame)?;
+    writeln!(writer, "TIME_SYSTEM = {}", metadata.time_system)
+}
+
+/// Milliseconds since the Unix epoch as `YYYY-MM-DDThh:mm:ss.sss`
+pub(crate) fn format_epoch(time_ms : u64) -> String {
+    let days = (time_ms / 86_400_000) as i64;
+    let ms_of_day = time_ms % 86_400_000;
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}",
+        year, month, day,
+        ms_of_day / 3_600_000, ms_of_day / 60_000 % 60, ms_of_day / 1000 % 60, ms_of_day % 1000)
+}
+
+/// Proleptic Gregorian (year, month, day) of a count of days since 1970-01-01
+fn civil_from_days(days : i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+pub(crate) fn days_from_civil(year : i64, month : u32, day : u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let day_of_year = (153 * shifted_month + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ earth, Anomaly, Body, Orbit };
+
+    #[test]
+    fn epochs_format_as_calendar_dates() {
+        assert_eq!(format_epoch(0), "1970-01-01T00:00:00.000");
+        assert_eq!(format_epoch(951_782_400_000 + 45_296_789), "2000-02-29T12:34:56.789");
+        assert_eq!(format_epoch(1_735_689_599_000), "2024-12-31T23:59:59.000");
+    }
+
+    #[test]
+    fn epochs_parse_back() {
+        for &time_ms in [0u64, 951_782_400_000 + 45_296_789, 1_735_689_599_000].iter() {
+            assert_eq!(parse_epoch(&format_epoch(time_ms)), Some(time_ms));
+        }
+        assert_eq!(parse_epoch("1969-12-31T23:59:59"), None);
+        assert_eq!(parse_epoch("2020-13-01T00:00:00"), None);
+    }
+
+    #[test]
+    fn omm_reads_mean_motion_and_round_trips() {
+        let text = "CCSDS_OMM_VERS = 2.0
+COMMENT ISS
+CREATION_DATE = 2020-065T16:00:00
+ORIGINATOR = TEST
+
+OBJECT_NAME = ISS (ZARYA)
+OBJECT_ID = 1998-067A
+CENTER_NAME = EARTH
+REF_FRAME = TEME
+TIME_SYSTEM = UTC
+MEAN_ELEMENT_THEORY = SGP4
+
+EPOCH = 2020-03-05T12:00:00.000
+MEAN_MOTION = 15.49 [rev/day]
+ECCENTRICITY = 0.0005
+INCLINATION = 51.64 [deg]
+RA_OF_ASC_NODE = 120.0
+ARG_OF_PERICENTER = 90.0
+MEAN_ANOMALY = 270.0
+";
+        let (orbit, anomaly) = Orbit::from_omm_kvn(text).unwrap();
+        let a = orbit.semimajor_axis().unwrap();
+        assert!((a - 6.797_787e6).abs() < 1.0, "{}", a);
+        assert!((orbit.inclination() - 51.64f64.to_radians()).abs() < 1e-12);
+        assert!((anomaly.mean_anomaly - 270f64.to_radians()).abs() < 1e-12);
+        assert_eq!(anomaly.time_ms, 1_583_409_600_000);
+
+        let body = Body::builder().gravitational_parameter(OMM_DEFAULT_GM * 1e9).radius(6.371e6).build().unwrap();
+        let metadata = MessageMetadata {
+            originator : "TEST", creation_ms : 0, object_name : "ISS (ZARYA)", object_id : "1998-067A",
+            center_name : "EARTH", ref_frame : "TEME", time_system : "UTC"
+        };
+        let mut written = Vec::new();
+        let error = orbit.write_omm(&mut written, &body, &anomaly, &metadata, "SGP4").unwrap_err();
+        assert_eq!((error.kind(), written.len()), (io::ErrorKind::InvalidInput, 0));
+        orbit.write_omm(&mut written, &body, &anomaly, &metadata, "DSST").unwrap();
+        let (again, again_anomaly) = Orbit::from_omm_kvn(&String::from_utf8(written).unwrap()).unwrap();
+        assert!((again.semimajor_axis().unwrap() - a).abs() < 1e-2);
+        assert!((again.ascending_node() - orbit.ascending_node()).abs() < 1e-7);
+        assert_eq!(again_anomaly.time_ms, anomaly.time_ms);
+
+        assert_eq!(Orbit::from_omm_kvn("EPOCH = 2020-03-05T12:00:00").err(), Some(OrbitError::MissingField("MEAN_MOTION".to_string())));
+    }
+
+    #[test]
+    fn oem_lists_every_sample() {
+        let body = earth();
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        let start = Anomaly::from_true(&orbit, 0.0, 946_728_000_000);
+        let ephemeris = orbit.build_ephemeris(&body, &start, 60.0, 3);
+        let metadata = MessageMetadata {
+            originator : "TEST", creation_ms : 946_728_000_000, object_name : "SAT", object_id : "2000-001A",
+            center_name : "EARTH", ref_frame : "EME2000", time_system : "UTC"
+        };
+        let mut text = Vec::new();
+        ephemeris.write_ephemeris(&mut text, &metadata).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.starts_with("CCSDS_OEM_VERS = 2.0\n"));
+        assert!(text.contains("REF_FRAME = EME2000\nTIME_SYSTEM = UTC\n"));
+        assert!(text.contains("STOP_TIME = 2000-01-01T12:02:00.000\n"));
+        assert!(text.contains("\n2000-01-01T12:00:00.000 7000.000000 "));
+        assert_eq!(text.lines().filter(|line| line.starts_with("2000-")).count(), 3);
+    }
+}
@@ -0,0 +1,90 @@
+//! Chebyshev polynomial fits of position, the compact representation JPL SPK ephemerides use
+use std::f64::consts::{ PI };
+use crate::{ Anomaly, Body, Orbit };
+
+/// Positions checked between the nodes, per coefficient, by `Orbit::chebyshev_error`
+const ERROR_SAMPLES_PER_COEFFICIENT : usize = 8;
+
+/// Value of `Σ c_j T_j(tau)` for `tau` in `[-1, 1]`, by Clenshaw's recurrence
+pub fn eval_chebyshev(coefficients : &[f64], tau : f64) -> f64 {
+    let (mut next, mut after) = (0.0, 0.0);
+    for coefficient in coefficients.iter().skip(1).rev() {
+        let current = 2.0 * tau * next - after + coefficient;
+        after = next;
+        next = current;
+    }
+    coefficients.first().map_or(0.0, |first| tau * next - after + first)
+}
+
+impl Orbit {
+
+    /// Coefficients of degree-`degree` Chebyshev polynomials in x, y and z position (meters) over
+    /// the `duration` seconds after `start`, interpolating at the Chebyshev nodes. Evaluate with
+    /// `eval_chebyshev` at `tau = 2t/duration - 1`, t being seconds after `start`. The error falls
+    /// off geometrically with degree once the interval is short against the period; check it with
+    /// `chebyshev_error`.
+    pub fn fit_chebyshev(&self, body : &Body, start : &Anomaly, duration : f64, degree : usize) -> [Vec<f64>; 3] {
+        let count = degree + 1;
+        let nodes : Vec<(f64, [f64; 3])> = (0..count)
+            .map(|k| {
+                let tau = (PI * (k as f64 + 0.5) / count as f64).cos();
+                let position = self.state_vector(body, &self.next_anomaly(body, start, (tau + 1.0) / 2.0 * duration)).position();
+                (tau, [position.x, position.y, position.z])
+            })
+            .collect();
+        let fit = |axis : usize| -> Vec<f64> {
+            (0..count)
+                .map(|j| {
+                    // Discrete orthogonality of T_j over the nodes
+                    let sum : f64 = nodes.iter().map(|(tau, position)| position[axis] * (j as f64 * tau.acos()).cos()).sum();
+                    if j == 0 { sum / count as f64 } else { 2.0 * sum / count as f64 }
+                })
+                .collect()
+        };
+        [fit(0), fit(1), fit(2)]
+    }
+
+    /// Largest distance in meters between the fitted and two-body positions over the interval of
+    /// `fit_chebyshev`, sampled finely between the nodes
+    pub fn chebyshev_error(&self, body : &Body, start : &Anomaly, duration : f64, coefficients : &[Vec<f64>; 3]) -> f64 {
+        let samples = ERROR_SAMPLES_PER_COEFFICIENT * coefficients[0].len().max(1);
+        (0..=samples)
+            .map(|j| {
+                let t = duration * j as f64 / samples as f64;
+                let tau = 2.0 * t / duration - 1.0;
+                let position = self.state_vector(body, &self.next_anomaly(body, start, t)).position();
+                let squared = [position.x, position.y, position.z].iter().zip(coefficients.iter())
+                    .map(|(actual, axis)| (actual - eval_chebyshev(axis, tau)).powi(2))
+                    .sum::<f64>();
+                squared.sqrt()
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ earth };
+
+    #[test]
+    fn clenshaw_matches_the_polynomials() {
+        // T_0 + 2 T_1 + 3 T_2 = 1 + 2τ + 3(2τ² - 1)
+        let tau = 0.3;
+        assert!((eval_chebyshev(&[1.0, 2.0, 3.0], tau) - (1.0 + 2.0 * tau + 3.0 * (2.0 * tau * tau - 1.0))).abs() < 1e-15);
+        assert_eq!(eval_chebyshev(&[], tau), 0.0);
+    }
+
+    #[test]
+    fn error_falls_with_degree() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.4, ascending_node : 1.0, inclination : 0.6, periapsis : 7.0e6, eccentricity : 0.1 };
+        let start = Anomaly::from_true(&orbit, 0.3, 0);
+        let duration = orbit.orbital_period(&body).unwrap() / 4.0;
+        let errors : Vec<f64> = [4, 8, 12].iter()
+            .map(|&degree| orbit.chebyshev_error(&body, &start, duration, &orbit.fit_chebyshev(&body, &start, duration, degree)))
+            .collect();
+        assert!(errors[0] > errors[1] && errors[1] > errors[2]);
+        assert!(errors[2] < 1e-3, "{:?}", errors);
+    }
+}
@@ -0,0 +1,129 @@
+//! Close approaches between two objects orbiting the same body
+use std::f64::consts::{ PI };
+use crate::{ Anomaly, Body, Orbit, Vector3 };
+
+/// Coarse scan samples per revolution of the faster orbit
+const SAMPLES_PER_REVOLUTION : f64 = 72.0;
+const MAX_SAMPLES : usize = 1_000_000;
+const GOLDEN_SECTION_ITERATIONS : usize = 100;
+/// True anomaly samples across the remaining path for `closest_approach_to`
+const PATH_SAMPLES : usize = 720;
+
+impl Orbit {
+
+    /// (seconds after `a1.time_ms`, meters apart) of the closest approach between an object at `a1` on
+    /// this orbit and one at `a2` on `other` during the `search_window` seconds following `a1`. Both
+    /// are propagated as two-body motion; the separation is scanned coarsely, several dozen samples
+    /// per revolution of the faster orbit, and the best minimum refined by golden-section search. A
+    /// close approach between samples that never shows up in the scan can be missed, so keep the
+    /// window down to the span of interest.
+    pub fn time_of_closest_approach(&self, other : &Orbit, body : &Body, a1 : &Anomaly, a2 : &Anomaly, search_window : f64) -> (f64, f64) {
+        let offset = (a1.time_ms as f64 - a2.time_ms as f64) / 1000.0;
+        let position = |orbit : &Orbit, start : &Anomaly, t : f64| orbit.state_vector(body, &orbit.next_anomaly(body, start, t)).position();
+        let separation = |t : f64| (position(self, a1, t) - position(other, a2, t + offset)).magnitude();
+
+        let shortest_period = self.time_scale(body).min(other.time_scale(body));
+        let samples = ((search_window / shortest_period * SAMPLES_PER_REVOLUTION).ceil() as usize).clamp(2, MAX_SAMPLES);
+        let step = search_window / samples as f64;
+        let (best, _) = (0..=samples)
+            .map(|j| (j, separation(j as f64 * step)))
+            .fold((0, f64::INFINITY), |best, sample| if sample.1 < best.1 { sample } else { best });
+
+        let time = golden_section_minimum(separation, best.saturating_sub(1) as f64 * step, (best + 1).min(samples) as f64 * step);
+        (time, separation(time))
+    }
+
+    /// (seconds after `anomaly.time_ms`, meters) of the closest approach to a fixed `point` of an
+    /// object at `anomaly`, over the next revolution of a closed orbit or the rest of the path out
+    /// along an open one. Scanned in true anomaly, several hundred samples over the path, and the
+    /// best minimum refined by golden-section search; the same caveat about minima between samples
+    /// applies as for `time_of_closest_approach`.
+    pub fn closest_approach_to(&self, body : &Body, point : Vector3, anomaly : &Anomaly) -> (f64, f64) {
+        let (start, sweep) = match self.orbital_period(body) {
+            Some(_) => (anomaly.true_anomaly, 2.0 * PI),
+            // Short of the asymptote, where the position runs off to infinity
+            None => {
+                let start = (anomaly.true_anomaly + PI).rem_euclid(2.0 * PI) - PI;
+                let asymptote = (-1.0 / self.eccentricity()).acos() * (1.0 - 1e-9);
+                (start, (asymptote - start).max(0.0))
+            }
+        };
+        let distance = |nu : f64| (self.position_at(body, nu) - point).magnitude();
+
+        let step = sweep / PATH_SAMPLES as f64;
+        let (best, _) = (0..=PATH_SAMPLES)
+            .map(|j| (j, distance(start + j as f64 * step)))
+            .fold((0, f64::INFINITY), |best, sample| if sample.1 < best.1 { sample } else { best });
+
+        let nu = golden_section_minimum(distance, start + best.saturating_sub(1) as f64 * step, start + (best + 1).min(PATH_SAMPLES) as f64 * step);
+        (self.time_of_flight(body, start, nu), distance(nu))
+    }
+
+    /// Whether an object at `anomaly` enters the sphere of `radius` meters about `center` over the
+    /// span searched by `closest_approach_to`, which gives the time and distance of the approach
+    pub fn intersects_sphere(&self, body : &Body, center : Vector3, radius : f64, anomaly : &Anomaly) -> bool {
+        self.closest_approach_to(body, center, anomaly).1 <= radius
+    }
+
+    /// Seconds over which the orbit changes appreciably: the period, or for open orbits the time
+    /// to sweep a radian of mean anomaly
+    fn time_scale(&self, body : &Body) -> f64 {
+        self.orbital_period(body).unwrap_or_else(|| 1.0 / self.mean_motion(body))
+    }
+}
+
+/// Where `f` is least between `low` and `high`, narrowed by golden-section search on the
+/// assumption that it has a single minimum there
+fn golden_section_minimum(f : impl Fn(f64) -> f64, low : f64, high : f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut low, mut high) = (low, high);
+    for _ in 0..GOLDEN_SECTION_ITERATIONS {
+        let left = high - ratio * (high - low);
+        let right = low + ratio * (high - low);
+        if f(left) < f(right) {
+            high = right;
+        } else {
+            low = left;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ earth, Anomaly, Orbit };
+
+    #[test]
+    fn crossing_orbits_meet_at_the_node() {
+        let body = earth();
+        let first = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        let second = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 1.0, semimajor_axis : 7.0e6 };
+        let period = first.orbital_period(&body).unwrap();
+        // Both reach the ascending node (true anomaly zero) a third of a period after the start,
+        // the second object's clock starting 100 seconds later
+        let a1 = Anomaly::from_true(&first, -2.0 * std::f64::consts::PI / 3.0, 1_000_000);
+        let a2 = Anomaly::from_mean(&second, -2.0 * std::f64::consts::PI / 3.0 + 100.0 * second.mean_motion(&body), 1_100_000);
+        let (time, distance) = first.time_of_closest_approach(&second, &body, &a1, &a2, period / 2.0);
+        assert!((time - period / 3.0).abs() < 1e-3, "{} vs {}", time, period / 3.0);
+        assert!(distance < 1.0);
+    }
+
+    #[test]
+    fn sphere_on_the_path_is_entered() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 0.2 };
+        let start = Anomaly::from_true(&orbit, 1.0, 0);
+        let station = orbit.position_at(&body, 2.5) + orbit.normal() * 500.0;
+        let (time, distance) = orbit.closest_approach_to(&body, station, &start);
+        assert!((time - orbit.time_of_flight(&body, 1.0, 2.5)).abs() < 1e-2, "{}", time);
+        assert!((distance - 500.0).abs() < 1e-3);
+        assert!(orbit.intersects_sphere(&body, station, 1000.0, &start));
+        assert!(!orbit.intersects_sphere(&body, station, 400.0, &start));
+
+        // Already past it on the way out
+        let escape = Orbit::Hyperbolic { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 1.5 };
+        let behind = escape.position_at(&body, -0.5);
+        assert!(escape.intersects_sphere(&body, behind, 1.0, &Anomaly::from_true(&escape, -1.0, 0)));
+        assert!(!escape.intersects_sphere(&body, behind, 1.0e5, &Anomaly::from_true(&escape, 0.5, 0)));
+    }
+}
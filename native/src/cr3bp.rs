@@ -0,0 +1,98 @@
+//! The circular restricted three-body problem. Quantities are nondimensional in the frame rotating
+//! with the primaries: unit distance between them, unit total mass, and unit angular velocity about
+//! +z, so one revolution takes 2π. The larger primary, of mass `1 - μ`, sits at `(-μ, 0, 0)` and
+//! the smaller, of mass `μ`, at `(1 - μ, 0, 0)`.
+use crate::{ Vector3 };
+
+const LAGRANGE_ITERATIONS : usize = 50;
+
+/// Jacobi integral `x² + y² + 2(1 - μ)/r₁ + 2μ/r₂ - v²` of a test particle at rotating-frame
+/// `position` and `velocity`, for mass ratio `mass_ratio` = μ, the smaller primary's share of the
+/// total mass. Constant along the motion; where `v²` would have to be negative for a given value
+/// the particle can't go, which bounds its motion by the zero-velocity surfaces.
+pub fn jacobi_constant(position : Vector3, velocity : Vector3, mass_ratio : f64) -> f64 {
+    let r1 = (position - Vector3::new(-mass_ratio, 0.0, 0.0)).magnitude();
+    let r2 = (position - Vector3::new(1.0 - mass_ratio, 0.0, 0.0)).magnitude();
+    position.x * position.x + position.y * position.y + 2.0 * (1.0 - mass_ratio) / r1 + 2.0 * mass_ratio / r2
+        - velocity.magnitude_squared()
+}
+
+/// Rotating-frame positions of the libration points L1 to L5 for mass ratio μ in `(0, 0.5]`. L1
+/// lies between the primaries, L2 beyond the smaller and L3 beyond the larger, each a root of the
+/// quintic for equilibrium on the x axis found by Newton-Raphson from Hill's approximation; L4 and
+/// L5 lead and trail the smaller primary at the apexes of equilateral triangles.
+pub fn lagrange_points(mass_ratio : f64) -> [Vector3; 5] {
+    let mu = mass_ratio;
+    // Net rotating-frame force along the x axis and its derivative
+    let force = |x : f64| {
+        let (d1, d2) = (x + mu, x - 1.0 + mu);
+        x - (1.0 - mu) * d1 / d1.abs().powi(3) - mu * d2 / d2.abs().powi(3)
+    };
+    let slope = |x : f64| 1.0 + 2.0 * (1.0 - mu) / (x + mu).abs().powi(3) + 2.0 * mu / (x - 1.0 + mu).abs().powi(3);
+    let collinear = |guess : f64| {
+        let mut x = guess;
+        for _ in 0..LAGRANGE_ITERATIONS {
+            let step = force(x) / slope(x);
+            x -= step;
+            if step.abs() < 1e-15 {
+                break;
+            }
+        }
+        Vector3::new(x, 0.0, 0.0)
+    };
+    let hill = (mu / 3.0).cbrt();
+    let height = 3f64.sqrt() / 2.0;
+    [
+        collinear(1.0 - mu - hill),
+        collinear(1.0 - mu + hill),
+        collinear(-1.0 - 5.0 * mu / 12.0),
+        Vector3::new(0.5 - mu, height, 0.0),
+        Vector3::new(0.5 - mu, -height, 0.0)
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rotating-frame equations of motion
+    fn acceleration(position : Vector3, velocity : Vector3, mu : f64) -> Vector3 {
+        let to_first = position - Vector3::new(-mu, 0.0, 0.0);
+        let to_second = position - Vector3::new(1.0 - mu, 0.0, 0.0);
+        let gravity = to_first * (-(1.0 - mu) / to_first.magnitude().powi(3)) + to_second * (-mu / to_second.magnitude().powi(3));
+        gravity + Vector3::new(position.x + 2.0 * velocity.y, position.y - 2.0 * velocity.x, 0.0)
+    }
+
+    #[test]
+    fn jacobi_constant_is_conserved() {
+        let mu = 0.012_15;
+        let (mut position, mut velocity) = (Vector3::new(0.5, 0.1, 0.05), Vector3::new(0.1, 0.6, 0.0));
+        let initial = jacobi_constant(position, velocity, mu);
+        let h = 1e-4;
+        for _ in 0..10_000 {
+            let (r1, v1) = (velocity, acceleration(position, velocity, mu));
+            let (r2, v2) = (velocity + v1 * (h / 2.0), acceleration(position + r1 * (h / 2.0), velocity + v1 * (h / 2.0), mu));
+            let (r3, v3) = (velocity + v2 * (h / 2.0), acceleration(position + r2 * (h / 2.0), velocity + v2 * (h / 2.0), mu));
+            let (r4, v4) = (velocity + v3 * h, acceleration(position + r3 * h, velocity + v3 * h, mu));
+            position = position + (r1 + r2 * 2.0 + r3 * 2.0 + r4) * (h / 6.0);
+            velocity = velocity + (v1 + v2 * 2.0 + v3 * 2.0 + v4) * (h / 6.0);
+        }
+        assert!((jacobi_constant(position, velocity, mu) - initial).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lagrange_points_are_equilibria() {
+        let mu = 0.012_15;
+        let points = lagrange_points(mu);
+        for point in points.iter() {
+            assert!(acceleration(*point, Vector3::zero(), mu).magnitude() < 1e-12, "{:?}", point);
+        }
+        // Earth-Moon values
+        let expected = [0.836_9, 1.155_7, -1.005_1];
+        for (point, &x) in points.iter().zip(expected.iter()) {
+            assert!((point.x - x).abs() < 1e-4, "{} vs {}", point.x, x);
+        }
+        assert!(points[0].x < 1.0 - mu && points[1].x > 1.0 - mu);
+        assert!(points[3].y > 0.0 && points[4].y < 0.0);
+    }
+}
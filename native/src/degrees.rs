@@ -0,0 +1,59 @@
+//! Degree conveniences. Every angle the crate stores, takes, and returns is in radians unless its
+//! name ends in `_deg`; these convert at the boundary and nothing more.
+use crate::{ Orbit };
+
+impl Orbit {
+
+    /// `from_elements` with the angles in degrees
+    pub fn from_elements_deg(periapsis : f64, eccentricity : f64, inclination : f64, ascending_node : f64, angle_of_periapsis : f64) -> Orbit {
+        Orbit::from_elements(periapsis, eccentricity, inclination.to_radians(), ascending_node.to_radians(), angle_of_periapsis.to_radians())
+    }
+
+    /// Degrees
+    pub fn inclination_deg(&self) -> f64 {
+        self.inclination().to_degrees()
+    }
+
+    /// Degrees
+    pub fn ascending_node_deg(&self) -> f64 {
+        self.ascending_node().to_degrees()
+    }
+
+    /// Degrees
+    pub fn angle_of_periapsis_deg(&self) -> f64 {
+        self.angle_of_periapsis().to_degrees()
+    }
+
+    /// Same orbit at an inclination of `inclination` degrees
+    pub fn with_inclination_deg(&self, inclination : f64) -> Orbit {
+        self.with_orientation(inclination.to_radians(), self.ascending_node(), self.angle_of_periapsis())
+    }
+
+    /// Same orbit with its ascending node at `ascending_node` degrees
+    pub fn with_ascending_node_deg(&self, ascending_node : f64) -> Orbit {
+        self.with_orientation(self.inclination(), ascending_node.to_radians(), self.angle_of_periapsis())
+    }
+
+    /// Same orbit with its angle of periapsis at `angle_of_periapsis` degrees
+    pub fn with_angle_of_periapsis_deg(&self, angle_of_periapsis : f64) -> Orbit {
+        self.with_orientation(self.inclination(), self.ascending_node(), angle_of_periapsis.to_radians())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ Orbit };
+
+    #[test]
+    fn degrees_convert_at_the_boundary() {
+        let orbit = Orbit::from_elements_deg(7.0e6, 0.1, 51.6, 120.0, 90.0);
+        assert!((orbit.inclination() - 51.6f64.to_radians()).abs() < 1e-15);
+        assert!((orbit.ascending_node_deg() - 120.0).abs() < 1e-12);
+        assert!((orbit.angle_of_periapsis_deg() - 90.0).abs() < 1e-12);
+        let tilted = orbit.with_inclination_deg(98.0).with_ascending_node_deg(10.0).with_angle_of_periapsis_deg(270.0);
+        assert!((tilted.inclination_deg() - 98.0).abs() < 1e-12);
+        assert!((tilted.ascending_node() - 10f64.to_radians()).abs() < 1e-15);
+        assert!((tilted.angle_of_periapsis_deg() - 270.0).abs() < 1e-12);
+        assert_eq!((tilted.periapsis(), tilted.eccentricity()), (7.0e6, 0.1));
+    }
+}
@@ -0,0 +1,273 @@
+//! Fitting orbits to observations
+use crate::{ offset_time_ms, Anomaly, Body, Orbit, Vector3 };
+
+const MAX_ITERATIONS : usize = 25;
+/// Converged once no parameter moves by more than this fraction of its finite-difference step
+const STEP_TOLERANCE : f64 = 1e-6;
+/// Levenberg damping added to the diagonal of the normal equations, relative to that diagonal
+const DAMPING : f64 = 1e-9;
+
+impl Orbit {
+
+    /// This orbit and the epoch `anomaly` adjusted by Gauss-Newton least squares to a set of
+    /// (seconds after `anomaly.time_ms`, range-rate in meters/second) observations, starting from
+    /// the current values, along with the post-fit residuals (observed - computed) in the same
+    /// order. Range-rates are taken from the body's center, so they are the radial velocity `ṙ`: that
+    /// fixes the periapsis, eccentricity, and the mean anomaly at the epoch, which are fitted, but
+    /// says nothing of how the plane or the ellipse within it are turned, so the three orientation
+    /// angles are kept.
+    pub fn refine_from_range_rates(&self, anomaly : &Anomaly, observations : &[(f64, f64)], body : &Body) -> (Orbit, Anomaly, Vec<f64>) {
+        let (inclination, ascending_node, angle_of_periapsis) = (self.inclination(), self.ascending_node(), self.angle_of_periapsis());
+        let orbit_from = |parameters : &[f64; 3]| Orbit::from_elements(parameters[0], parameters[1].max(0.0), inclination, ascending_node, angle_of_periapsis);
+        let residuals = |parameters : &[f64; 3]| -> Vec<f64> {
+            let orbit = orbit_from(parameters);
+            let mean_motion = orbit.mean_motion(body);
+            observations.iter()
+                .map(|&(t, range_rate)| {
+                    let at = Anomaly::from_mean(&orbit, parameters[2] + mean_motion * t, anomaly.time_ms);
+                    let (_, speed, flight_path_angle) = orbit.kinematics(body, &at);
+                    range_rate - speed * flight_path_angle.sin()
+                })
+                .collect()
+        };
+
+        let start = [self.periapsis(), self.eccentricity(), anomaly.mean_anomaly];
+        let parameters = gauss_newton(start, |parameters| [parameters[0] * 1e-7, 1e-7, 1e-7], residuals, |parameters| parameters[1] = parameters[1].max(0.0));
+        let orbit = orbit_from(&parameters);
+        let epoch = Anomaly::from_mean(&orbit, parameters[2], anomaly.time_ms);
+        (orbit, epoch, residuals(&parameters))
+    }
+}
+
+impl Orbit {
+
+    /// Orbit through a single radar observation from a site at body-fixed `observer` (meters), of
+    /// `range` (m) and `range_rate` (m/s), azimuth clockwise from north and elevation above the
+    /// horizon (radians) with their rates (radians/second). The horizon is normal to the body's
+    /// ellipsoid at the site. The body-fixed frame is taken to be `gmst` radians ahead of the inertial
+    /// one about z, and the site's own motion with the body's rotation is added to the observed
+    /// velocity before finding the orbit through the inertial state.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_radar(range : f64, range_rate : f64, azimuth : f64, elevation : f64, azimuth_rate : f64, elevation_rate : f64, observer : Vector3, gmst : f64, body : &Body) -> Orbit {
+        let (up, east, north) = horizon_axes(observer, body);
+        let (sin_az, cos_az) = azimuth.sin_cos();
+        let (sin_el, cos_el) = elevation.sin_cos();
+        let line_of_sight = east * (cos_el * sin_az) + north * (cos_el * cos_az) + up * sin_el;
+        let along_azimuth = east * (cos_el * cos_az) - north * (cos_el * sin_az);
+        let along_elevation = up * cos_el - east * (sin_el * sin_az) - north * (sin_el * cos_az);
+
+        let position = observer + line_of_sight * range;
+        let relative_velocity = line_of_sight * range_rate + (along_azimuth * azimuth_rate + along_elevation * elevation_rate) * range;
+        let site_motion = Vector3::k_hat().cross(position) * body.rotation_rate();
+        Orbit::from_inertial(position.rotate_z(gmst), (relative_velocity + site_motion).rotate_z(gmst), 0, body).0
+    }
+}
+
+impl Orbit {
+
+    /// Two-body orbit best fitting time-tagged (seconds, position in meters) samples in least
+    /// squares, with the anomaly at the first sample and the RMS of the post-fit position residuals
+    /// in meters. The state at the middle sample is seeded with a central-difference velocity from its
+    /// neighbours, so samples should be dense, a small fraction of the period apart, and refined by
+    /// Gauss-Newton over all six components. The anomaly is stamped with the first sample's time in
+    /// milliseconds. None with fewer than three samples or times that don't strictly increase.
+    pub fn fit_from_positions(samples : &[(f64, Vector3)], body : &Body) -> Option<(Orbit, Anomaly, f64)> {
+        if samples.len() < 3 || !samples.windows(2).all(|pair| pair[1].0 > pair[0].0) {
+            return None;
+        }
+        let middle = samples.len() / 2;
+        let (before, (t0, r0), after) = (samples[middle - 1], samples[middle], samples[middle + 1]);
+        let seed = (after.1 - before.1) * (1.0 / (after.0 - before.0));
+
+        let parameters_of = |position : Vector3, velocity : Vector3| [position.x, position.y, position.z, velocity.x, velocity.y, velocity.z];
+        let orbit_of = |p : &[f64; 6]| Orbit::from_inertial(Vector3::new(p[0], p[1], p[2]), Vector3::new(p[3], p[4], p[5]), 0, body);
+        let residuals = |p : &[f64; 6]| -> Vec<f64> {
+            let (orbit, anomaly) = orbit_of(p);
+            samples.iter()
+                .flat_map(|&(t, observed)| {
+                    let computed = orbit.state_vector(body, &orbit.next_anomaly(body, &anomaly, t - t0)).position();
+                    let miss = observed - computed;
+                    vec![miss.x, miss.y, miss.z]
+                })
+                .collect()
+        };
+
+        let (position_step, velocity_step) = (r0.magnitude() * 1e-8, seed.magnitude() * 1e-8);
+        let differences = [position_step, position_step, position_step, velocity_step, velocity_step, velocity_step];
+        let parameters = gauss_newton(parameters_of(r0, seed), |_| differences, residuals, |_| ());
+
+        let (orbit, at_middle) = orbit_of(&parameters);
+        let first = samples[0].0;
+        let mut anomaly = orbit.next_anomaly(body, &at_middle, first - t0);
+        anomaly.time_ms = offset_time_ms(0, first);
+        let misses = residuals(&parameters);
+        let rms = (misses.iter().map(|miss| miss * miss).sum::<f64>() / samples.len() as f64).sqrt();
+        Some((orbit, anomaly, rms))
+    }
+}
+
+/// (up, east, north) unit vectors at a body-fixed site, up along the ellipsoid normal. East is
+/// taken along the y axis at the poles.
+fn horizon_axes(site : Vector3, body : &Body) -> (Vector3, Vector3, Vector3) {
+    let polar_squared = (1.0 - body.flattening).powi(2);
+    let up = Vector3::new(site.x, site.y, site.z / polar_squared).unit();
+    let east = Vector3::k_hat().cross(up);
+    let east = if east.magnitude() > 1e-12 { east.unit() } else { Vector3::j_hat() };
+    (up, east, up.cross(east))
+}
+
+/// `parameters` adjusted by damped Gauss-Newton to minimize the sum of squared `residuals`
+/// (observed - computed), the Jacobian taken by forward differences of the sizes `differences` gives
+/// at the current parameters. `constrain` pulls the parameters back into their domain after each
+/// step. Stops once no parameter moves by more than `STEP_TOLERANCE` of its difference, after
+/// `MAX_ITERATIONS`, or when the normal equations are singular.
+fn gauss_newton<const N : usize>(mut parameters : [f64; N], differences : impl Fn(&[f64; N]) -> [f64; N], residuals : impl Fn(&[f64; N]) -> Vec<f64>, constrain : impl Fn(&mut [f64; N])) -> [f64; N] {
+    let dot = |a : &[f64], b : &[f64]| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>();
+    for _ in 0..MAX_ITERATIONS {
+        let differences = differences(&parameters);
+        let current = residuals(&parameters);
+        // Columns of the Jacobian of the computed values, by forward difference
+        let columns : Vec<Vec<f64>> = (0..N)
+            .map(|j| {
+                let mut shifted = parameters;
+                shifted[j] += differences[j];
+                residuals(&shifted).iter().zip(current.iter()).map(|(moved, base)| (base - moved) / differences[j]).collect()
+            })
+            .collect();
+        let mut normal = [[0.0; N]; N];
+        let mut right = [0.0; N];
+        for j in 0..N {
+            for l in 0..N {
+                normal[j][l] = dot(&columns[j], &columns[l]);
+            }
+            right[j] = dot(&columns[j], &current);
+        }
+        for (j, row) in normal.iter_mut().enumerate() {
+            row[j] += DAMPING * row[j] + f64::MIN_POSITIVE;
+        }
+        let step = match solve_linear(normal, right) {
+            Some(step) => step,
+            None => break
+        };
+        for j in 0..N {
+            parameters[j] += step[j];
+        }
+        constrain(&mut parameters);
+        if (0..N).all(|j| step[j].abs() < STEP_TOLERANCE * differences[j]) {
+            break;
+        }
+    }
+    parameters
+}
+
+/// `x` with `a·x = b` by Gaussian elimination with partial pivoting, None if `a` is singular
+fn solve_linear<const N : usize>(mut a : [[f64; N]; N], mut b : [f64; N]) -> Option<[f64; N]> {
+    for column in 0..N {
+        let pivot = (column..N).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+        if a[pivot][column] == 0.0 || !a[pivot][column].is_finite() {
+            return None;
+        }
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+        let pivot_row = a[column];
+        for row in column + 1..N {
+            let factor = a[row][column] / pivot_row[column];
+            for (value, above) in a[row].iter_mut().zip(pivot_row.iter()).skip(column) {
+                *value -= factor * above;
+            }
+            b[row] -= factor * b[column];
+        }
+    }
+    let mut x = [0.0; N];
+    for row in (0..N).rev() {
+        let known : f64 = (row + 1..N).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - known) / a[row][row];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ earth };
+    use crate::geodesy::{ geodetic_to_ecef };
+
+    #[test]
+    fn range_rates_recover_the_ellipse() {
+        let body = earth();
+        let truth = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 0.2, inclination : 0.9, periapsis : 6.9e6, eccentricity : 0.1 };
+        let epoch = Anomaly::from_mean(&truth, 0.4, 5_000);
+        let period = truth.orbital_period(&body).unwrap();
+        let observations : Vec<(f64, f64)> = (0..40)
+            .map(|j| {
+                let t = j as f64 / 40.0 * 1.5 * period;
+                let (_, speed, flight_path_angle) = truth.kinematics(&body, &truth.next_anomaly(&body, &epoch, t));
+                (t, speed * flight_path_angle.sin())
+            })
+            .collect();
+
+        let guess = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 0.2, inclination : 0.9, periapsis : 6.92e6, eccentricity : 0.12 };
+        let (fitted, fitted_epoch, residuals) = guess.refine_from_range_rates(&Anomaly::from_mean(&guess, 0.45, 5_000), &observations, &body);
+        assert!((fitted.periapsis() - 6.9e6).abs() < 1e-2, "{}", fitted.periapsis());
+        assert!((fitted.eccentricity() - 0.1).abs() < 1e-9);
+        assert!((fitted_epoch.mean_anomaly - 0.4).abs() < 1e-9);
+        assert_eq!(fitted_epoch.time_ms, 5_000);
+        assert_eq!(residuals.len(), observations.len());
+        assert!(residuals.iter().all(|residual| residual.abs() < 1e-6));
+    }
+
+    #[test]
+    fn radar_observation_recovers_the_orbit() {
+        let body = Body::builder().mass(5.972e24).radius(6.378_137e6).rotation_period(86_164.1).flattening(1.0 / 298.257).build().unwrap();
+        let truth = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 0.2, inclination : 0.9, periapsis : 6.9e6, eccentricity : 0.05 };
+        let gmst = 1.3;
+        let site = geodetic_to_ecef(0.6, 0.1, 50.0, &body);
+        let state = truth.state_vector(&body, &Anomaly::from_true(&truth, 0.3, 0));
+
+        // What the radar sees: the target relative to the site in the turning body-fixed frame
+        let (up, east, north) = horizon_axes(site, &body);
+        let relative = |t : f64| {
+            let inertial = state.position() + state.velocity() * t;
+            inertial.rotate_z(-gmst - body.rotation_rate() * t) - site
+        };
+        let angles = |t : f64| {
+            let seen = relative(t);
+            (seen.dot(east).atan2(seen.dot(north)), (seen.dot(up) / seen.magnitude()).asin(), seen.magnitude())
+        };
+        let h = 1e-3;
+        let ((az, el, range), (az_after, el_after, range_after), (az_before, el_before, range_before)) = (angles(0.0), angles(h), angles(-h));
+        let orbit = Orbit::from_radar(range, (range_after - range_before) / (2.0 * h), az, el,
+            (az_after - az_before) / (2.0 * h), (el_after - el_before) / (2.0 * h), site, gmst, &body);
+
+        assert!((orbit.periapsis() - truth.periapsis()).abs() < 1.0, "{}", orbit.periapsis());
+        assert!((orbit.eccentricity() - truth.eccentricity()).abs() < 1e-6);
+        assert!((orbit.inclination() - truth.inclination()).abs() < 1e-6);
+        assert!((orbit.ascending_node() - truth.ascending_node()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn positions_alone_recover_the_orbit() {
+        let body = earth();
+        let truth = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 0.2, inclination : 0.9, periapsis : 6.9e6, eccentricity : 0.1 };
+        let epoch = Anomaly::from_true(&truth, 0.4, 0);
+        let samples : Vec<(f64, Vector3)> = (0..30)
+            .map(|j| {
+                let t = 120.0 * j as f64;
+                (1_000.0 + t, truth.state_vector(&body, &truth.next_anomaly(&body, &epoch, t)).position())
+            })
+            .collect();
+        let (orbit, anomaly, rms) = Orbit::fit_from_positions(&samples, &body).unwrap();
+        assert!((orbit.periapsis() - 6.9e6).abs() < 1e-2, "{}", orbit.periapsis());
+        assert!((orbit.eccentricity() - 0.1).abs() < 1e-9);
+        assert!((orbit.inclination() - 0.9).abs() < 1e-9);
+        assert!((anomaly.true_anomaly - 0.4).abs() < 1e-9);
+        assert_eq!(anomaly.time_ms, 1_000_000);
+        assert!(rms < 1e-3, "{}", rms);
+        assert!(Orbit::fit_from_positions(&samples[..2], &body).is_none());
+        let mut repeated = samples.clone();
+        repeated[2].0 = repeated[1].0;
+        assert!(Orbit::fit_from_positions(&repeated, &body).is_none());
+        let reversed : Vec<_> = samples.iter().rev().cloned().collect();
+        assert!(Orbit::fit_from_positions(&reversed, &body).is_none());
+    }
+}
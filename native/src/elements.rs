@@ -0,0 +1,108 @@
+use std::f64::consts::{ PI };
+use crate::{ offset_time_ms, Anomaly, Body, Orbit, OrbitError, MS_PER_DAY, UNIX_EPOCH_JD };
+
+/// A published element set: the orbit itself plus, optionally, where the body was on it at an epoch,
+/// as ephemerides and TLEs give it. With the phase present the set alone locates the body at any time.
+pub struct KeplerianElements {
+    pub orbit : Orbit,
+    /// radians, M0. None when only the shape and orientation are known
+    pub mean_anomaly_at_epoch : Option<f64>,
+    /// milliseconds since the Unix epoch, on the same clock as `Anomaly::time_ms`
+    pub epoch_ms : u64
+}
+
+impl KeplerianElements {
+    pub fn new(orbit : Orbit) -> KeplerianElements {
+        KeplerianElements { orbit, mean_anomaly_at_epoch : None, epoch_ms : 0 }
+    }
+
+    pub fn with_epoch(orbit : Orbit, mean_anomaly_at_epoch : f64, epoch_ms : u64) -> KeplerianElements {
+        KeplerianElements { orbit, mean_anomaly_at_epoch : Some(mean_anomaly_at_epoch), epoch_ms }
+    }
+
+    /// Where the body sits at the epoch, None without a mean anomaly at epoch
+    pub fn epoch_anomaly(&self) -> Option<Anomaly> {
+        self.mean_anomaly_at_epoch.map(|mean| Anomaly::from_mean(&self.orbit, mean, self.epoch_ms))
+    }
+
+    /// Where the body sits at `time_ms`, before or after the epoch, None without a mean anomaly at epoch
+    pub fn anomaly_at(&self, body : &Body, time_ms : u64) -> Option<Anomaly> {
+        self.mean_anomaly_at_epoch.map(|mean_at_epoch| {
+            let elapsed = (time_ms as f64 - self.epoch_ms as f64) / 1000.0;
+            let mut mean = mean_at_epoch + self.orbit.mean_motion(body) * elapsed;
+            if self.orbit.orbital_period(body).is_some() {
+                mean = mean.rem_euclid(2.0 * PI);
+            }
+            Anomaly::from_mean(&self.orbit, mean, time_ms)
+        })
+    }
+}
+
+impl Orbit {
+
+    /// Orbit and anomaly at `epoch` (a Julian date, TDB for JPL's tables) from elements in the JPL
+    /// convention: semi-major axis `a` in meters, and inclination, longitude of the ascending node,
+    /// longitude of periapsis `ϖ = Ω + ω` and mean longitude `L = ϖ + M` in radians. The tables quote
+    /// AU and degrees; convert first. A negative inclination, as JPL gives for Earth, flips the node
+    /// by π and keeps both longitudes. `OrbitError::InvalidValue` for an eccentricity outside
+    /// `[0, 1)`, as the tables only cover closed orbits, or an epoch before the Unix epoch, which
+    /// `time_ms` can't hold.
+    pub fn from_jpl_elements(a : f64, e : f64, i : f64, longitude_of_ascending_node : f64, longitude_of_periapsis : f64, mean_longitude : f64, epoch : f64) -> Result<(Orbit, Anomaly), OrbitError> {
+        if e.is_nan() || !(0.0..1.0).contains(&e) {
+            return Err(OrbitError::InvalidValue { keyword : "eccentricity".to_string(), value : e.to_string() });
+        }
+        if epoch.is_nan() || epoch < UNIX_EPOCH_JD {
+            return Err(OrbitError::InvalidValue { keyword : "epoch".to_string(), value : epoch.to_string() });
+        }
+        let (inclination, ascending_node) = if i < 0.0 {
+            (-i, longitude_of_ascending_node + PI)
+        } else {
+            (i, longitude_of_ascending_node)
+        };
+        let ascending_node = ascending_node.rem_euclid(2.0 * PI);
+        let angle_of_periapsis = (longitude_of_periapsis - ascending_node).rem_euclid(2.0 * PI);
+        let mean_anomaly = (mean_longitude - longitude_of_periapsis).rem_euclid(2.0 * PI);
+        let orbit = Orbit::from_elements(a * (1.0 - e), e, inclination, ascending_node, angle_of_periapsis);
+        let time_ms = offset_time_ms(0, (epoch - UNIX_EPOCH_JD) * MS_PER_DAY / 1000.0);
+        let anomaly = Anomaly::from_mean(&orbit, mean_anomaly, time_ms);
+        Ok((orbit, anomaly))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ earth };
+
+    #[test]
+    fn epoch_phase_locates_the_body() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.1 };
+        let period_ms = (orbit.orbital_period(&body).unwrap() * 1000.0) as u64;
+        let elements = KeplerianElements::with_epoch(orbit, 1.0, 10 * period_ms);
+        assert!((elements.epoch_anomaly().unwrap().mean_anomaly - 1.0).abs() < 1e-12);
+        let before = elements.anomaly_at(&body, 7 * period_ms).unwrap();
+        assert!((before.mean_anomaly - 1.0).abs() < 1e-6);
+
+        let shape_only = KeplerianElements::new(Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 });
+        assert!(shape_only.anomaly_at(&body, 0).is_none());
+    }
+
+    #[test]
+    fn imports_earth_from_jpl_elements() {
+        // Standish, Keplerian Elements for Approximate Positions of the Major Planets, Table 1, J2000
+        let sun = Body::builder().gravitational_parameter(1.327_124_400_18e20).radius(6.957e8).build().unwrap();
+        let au = 1.495_978_707e11;
+        let (orbit, anomaly) = Orbit::from_jpl_elements(1.000_002_61 * au, 0.016_711_23, (-0.000_015_31f64).to_radians(), 0.0,
+            102.937_681_93f64.to_radians(), 100.464_571_66f64.to_radians(), 2_451_545.0).ok().unwrap();
+        let days = orbit.orbital_period(&sun).unwrap() / 86_400.0;
+        assert!((days - 365.256).abs() < 0.01, "{}", days);
+        assert!(orbit.inclination() >= 0.0);
+        assert!((orbit.longitude_of_periapsis() - 102.937_681_93f64.to_radians()).abs() < 1e-12);
+        assert!((orbit.mean_longitude(&anomaly) - 100.464_571_66f64.to_radians()).abs() < 1e-12);
+        assert_eq!(anomaly.time_ms, 946_728_000_000);
+
+        assert!(Orbit::from_jpl_elements(au, 1.0, 0.0, 0.0, 0.0, 0.0, 2_451_545.0).is_err());
+        assert!(Orbit::from_jpl_elements(au, 0.1, 0.0, 0.0, 0.0, 0.0, UNIX_EPOCH_JD - 1.0).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! Precomputed states of one orbit, for cheap repeated queries
+use crate::{ Anomaly, Body, Orbit, StateVector };
+
+/// States sampled at a fixed step, interpolated between by cubic Hermite splines
+pub struct Ephemeris {
+    /// milliseconds, the time of the first sample
+    start_ms : u64,
+    /// seconds between samples
+    step : f64,
+    states : Vec<StateVector>
+}
+
+impl Ephemeris {
+    /// The precomputed samples, in time order
+    pub fn samples(&self) -> &[StateVector] { &self.states }
+
+    /// State at `time_ms`, interpolated from the bracketing samples with each sample's velocity as the
+    /// derivative of position; the velocity returned is the derivative of that interpolant. None outside
+    /// the sampled span. With samples a small fraction of the period apart (tens per orbit) the error is
+    /// a few meters even at low altitude, falling as the step to the fourth power.
+    pub fn interpolate(&self, time_ms : u64) -> Option<StateVector> {
+        if time_ms < self.start_ms || self.states.is_empty() {
+            return None;
+        }
+        let position = (time_ms - self.start_ms) as f64 / 1000.0 / self.step;
+        let last = self.states.len() - 1;
+        if position > last as f64 {
+            return None;
+        }
+        if last == 0 {
+            return Some(self.states[0]);
+        }
+        let index = (position.floor() as usize).min(last - 1);
+        let (before, after) = (&self.states[index], &self.states[index + 1]);
+        let t = position - index as f64;
+        let h = self.step;
+
+        let (t2, t3) = (t * t, t * t * t);
+        let basis = [2.0 * t3 - 3.0 * t2 + 1.0, t3 - 2.0 * t2 + t, -2.0 * t3 + 3.0 * t2, t3 - t2];
+        let slope = [6.0 * t2 - 6.0 * t, 3.0 * t2 - 4.0 * t + 1.0, -6.0 * t2 + 6.0 * t, 3.0 * t2 - 2.0 * t];
+        let position = before.position() * basis[0] + before.velocity() * (h * basis[1])
+            + after.position() * basis[2] + after.velocity() * (h * basis[3]);
+        let velocity = before.position() * (slope[0] / h) + before.velocity() * slope[1]
+            + after.position() * (slope[2] / h) + after.velocity() * slope[3];
+        Some(StateVector::new(position, velocity, time_ms))
+    }
+}
+
+impl Orbit {
+
+    /// `count` states `step` seconds apart starting from `start`, for repeated interpolation by
+    /// `Ephemeris::interpolate` without solving Kepler's equation per query
+    pub fn build_ephemeris(&self, body : &Body, start : &Anomaly, step : f64, count : usize) -> Ephemeris {
+        let states = (0..count)
+            .map(|k| {
+                let anomaly = self.next_anomaly(body, start, k as f64 * step);
+                self.state_vector(body, &anomaly)
+            })
+            .collect();
+        Ephemeris { start_ms : start.time_ms, step, states }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ earth, Anomaly, Orbit };
+
+    #[test]
+    fn interpolation_matches_propagation() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.4, ascending_node : 1.0, inclination : 0.9, periapsis : 6.8e6, eccentricity : 0.05 };
+        let start = Anomaly::from_true(&orbit, 0.0, 1_000_000);
+        let ephemeris = orbit.build_ephemeris(&body, &start, 60.0, 100);
+        assert_eq!(ephemeris.samples().len(), 100);
+
+        for &offset_ms in [0u64, 17_300, 1_234_567, 5_940_000].iter() {
+            let interpolated = ephemeris.interpolate(start.time_ms + offset_ms).unwrap();
+            let exact = orbit.state_vector(&body, &orbit.next_anomaly(&body, &start, offset_ms as f64 / 1000.0));
+            assert!((interpolated.position() - exact.position()).magnitude() < 5.0);
+            assert!((interpolated.velocity() - exact.velocity()).magnitude() < 0.05);
+        }
+        assert!(ephemeris.interpolate(start.time_ms - 1).is_none());
+        assert!(ephemeris.interpolate(start.time_ms + 5_940_001).is_none());
+    }
+}
@@ -0,0 +1,277 @@
+//! Equinoctial elements, free of the classical elements' singularities at e = 0 and i = 0
+use std::f64::consts::{ PI };
+use crate::{ kepler, Anomaly, Body, KeplerSolver, NewtonSolver, Orbit, Vector3 };
+
+/// Direct equinoctial elements of a closed orbit. With ϖ = Ω + ω the longitude of periapsis,
+/// `h = e sin ϖ`, `k = e cos ϖ`, `p = tan(i/2) sin Ω`, `q = tan(i/2) cos Ω`, and `λ = ϖ + M`.
+/// These are singular only for retrograde equatorial orbits (i = π), where `tan(i/2)` diverges.
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct EquinoctialElements {
+    /// Semi-major axis, meters
+    pub a : f64,
+    pub h : f64,
+    pub k : f64,
+    pub p : f64,
+    pub q : f64,
+    /// Mean longitude, radians
+    pub lambda : f64
+}
+
+impl EquinoctialElements {
+
+    /// These elements `dt` seconds later (or earlier, if negative) under two-body motion, which only
+    /// advances the mean longitude. Unlike going through `Orbit::next_anomaly` nothing here refers to
+    /// the direction of periapsis, so precision holds up however close e and i are to zero.
+    pub fn propagate_equinoctial(&self, body : &Body, dt : f64) -> EquinoctialElements {
+        let mean_motion = (body.k() / self.a.powi(3)).sqrt();
+        EquinoctialElements { lambda : (self.lambda + mean_motion * dt).rem_euclid(2.0 * PI), ..*self }
+    }
+
+    /// Eccentric longitude F = E + ϖ, radians, solving Kepler's equation in equinoctial form,
+    /// `λ = F + h cos F - k sin F`. That is the classical equation in M = λ - ϖ, so it goes through
+    /// `NewtonSolver::solve` with its high-eccentricity start and bisection fallback. ϖ is taken as
+    /// zero for a circle, where any value gives the same F.
+    pub fn eccentric_longitude(&self) -> f64 {
+        let longitude_of_periapsis = self.h.atan2(self.k);
+        let eccentric = NewtonSolver::solve(self.h.hypot(self.k), self.lambda - longitude_of_periapsis, kepler::TOLERANCE);
+        (eccentric + longitude_of_periapsis).rem_euclid(2.0 * PI)
+    }
+}
+
+impl Orbit {
+
+    /// Equinoctial elements of this orbit at `anomaly`, None for open orbits
+    pub fn to_equinoctial(&self, anomaly : &Anomaly) -> Option<EquinoctialElements> {
+        let a = match *self {
+            Orbit::Circular { .. } | Orbit::Elliptical { .. } => self.semimajor_axis()?,
+            Orbit::Parabolic { .. } | Orbit::Hyperbolic { .. } => return None
+        };
+        let e = self.eccentricity();
+        let node = self.ascending_node();
+        let longitude_of_periapsis = node + self.angle_of_periapsis();
+        let tan_half = (self.inclination() / 2.0).tan();
+        Some(EquinoctialElements {
+            a,
+            h : e * longitude_of_periapsis.sin(),
+            k : e * longitude_of_periapsis.cos(),
+            p : tan_half * node.sin(),
+            q : tan_half * node.cos(),
+            lambda : (longitude_of_periapsis + anomaly.mean_anomaly).rem_euclid(2.0 * PI)
+        })
+    }
+
+    /// The orbit and anomaly, at `time_ms`, that equinoctial elements describe. Where the classical
+    /// angles are undefined they come out zero: Ω for an equatorial orbit and ω for a circular one.
+    pub fn from_equinoctial(elements : &EquinoctialElements, time_ms : u64) -> (Orbit, Anomaly) {
+        let EquinoctialElements { a, h, k, p, q, lambda } = *elements;
+        let e = h.hypot(k);
+        let tan_half = p.hypot(q);
+        let node = if tan_half > 0.0 { p.atan2(q).rem_euclid(2.0 * PI) } else { 0.0 };
+        let longitude_of_periapsis = if e > 0.0 { h.atan2(k) } else { node };
+        let orbit = Orbit::from_elements(
+            a * (1.0 - e),
+            e,
+            2.0 * tan_half.atan(),
+            node,
+            (longitude_of_periapsis - node).rem_euclid(2.0 * PI));
+        let anomaly = Anomaly::from_mean(&orbit, (lambda - longitude_of_periapsis).rem_euclid(2.0 * PI), time_ms);
+        (orbit, anomaly)
+    }
+}
+
+/// Modified equinoctial elements (Walker, Ireland and Owens 1985). With ϖ = Ω + ω,
+/// `f = e cos ϖ`, `g = e sin ϖ`, `h = tan(i/2) cos Ω`, `k = tan(i/2) sin Ω`, and the true longitude
+/// `L = ϖ + ν`. Defined for every conic, the semi-latus rectum taking the place of `a`, and singular
+/// only for retrograde equatorial orbits.
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct ModifiedEquinoctialElements {
+    /// Semi-latus rectum, meters
+    pub p : f64,
+    pub f : f64,
+    pub g : f64,
+    pub h : f64,
+    pub k : f64,
+    /// True longitude, radians
+    pub l : f64
+}
+
+impl ModifiedEquinoctialElements {
+
+    /// Time derivatives of (p, f, g, h, k, L), per second, under a perturbing acceleration
+    /// `accel_rsw` in meters/second² along the radial, in-plane transverse and orbit-normal
+    /// directions: Gauss's variational equations in modified equinoctial form (Betts, Practical
+    /// Methods for Optimal Control, §6.3). With no acceleration only L moves, at `h / r²`.
+    pub fn mee_rates(&self, body : &Body, accel_rsw : Vector3) -> [f64; 6] {
+        let ModifiedEquinoctialElements { p, f, g, h, k, l } = *self;
+        let (radial, transverse, normal) = (accel_rsw.x, accel_rsw.y, accel_rsw.z);
+        let (sin, cos) = l.sin_cos();
+        let w = 1.0 + f * cos + g * sin;
+        let s_squared = 1.0 + h * h + k * k;
+        let root = (p / body.k()).sqrt();
+        let out_of_plane = (h * sin - k * cos) * normal / w;
+        [
+            2.0 * p / w * root * transverse,
+            root * (radial * sin + ((w + 1.0) * cos + f) * transverse / w - g * out_of_plane),
+            root * (-radial * cos + ((w + 1.0) * sin + g) * transverse / w + f * out_of_plane),
+            root * s_squared * normal * cos / (2.0 * w),
+            root * s_squared * normal * sin / (2.0 * w),
+            (body.k() * p).sqrt() * (w / p).powi(2) + root * out_of_plane
+        ]
+    }
+}
+
+impl Orbit {
+
+    /// Modified equinoctial elements of this orbit at `anomaly`
+    pub fn to_mee(&self, anomaly : &Anomaly) -> ModifiedEquinoctialElements {
+        let e = self.eccentricity();
+        let node = self.ascending_node();
+        let longitude_of_periapsis = node + self.angle_of_periapsis();
+        let tan_half = (self.inclination() / 2.0).tan();
+        ModifiedEquinoctialElements {
+            p : self.parameter(),
+            f : e * longitude_of_periapsis.cos(),
+            g : e * longitude_of_periapsis.sin(),
+            h : tan_half * node.cos(),
+            k : tan_half * node.sin(),
+            l : (longitude_of_periapsis + anomaly.true_anomaly).rem_euclid(2.0 * PI)
+        }
+    }
+
+    /// The orbit and anomaly, at `time_ms`, that modified equinoctial elements describe, with the
+    /// undefined classical angles zero as in `from_equinoctial`. Open orbits take their true anomaly
+    /// in `[-π, π)`.
+    pub fn from_mee(elements : &ModifiedEquinoctialElements, time_ms : u64) -> (Orbit, Anomaly) {
+        let ModifiedEquinoctialElements { p, f, g, h, k, l } = *elements;
+        let e = f.hypot(g);
+        let tan_half = h.hypot(k);
+        let node = if tan_half > 0.0 { k.atan2(h).rem_euclid(2.0 * PI) } else { 0.0 };
+        let longitude_of_periapsis = if e > 0.0 { g.atan2(f) } else { node };
+        let orbit = Orbit::from_elements(
+            p / (1.0 + e),
+            e,
+            2.0 * tan_half.atan(),
+            node,
+            (longitude_of_periapsis - node).rem_euclid(2.0 * PI));
+        let true_anomaly = match orbit {
+            Orbit::Circular { .. } | Orbit::Elliptical { .. } => (l - longitude_of_periapsis).rem_euclid(2.0 * PI),
+            Orbit::Parabolic { .. } | Orbit::Hyperbolic { .. } => (l - longitude_of_periapsis + PI).rem_euclid(2.0 * PI) - PI
+        };
+        let anomaly = Anomaly::from_true(&orbit, true_anomaly, time_ms);
+        (orbit, anomaly)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ earth, StateVector };
+
+    #[test]
+    fn equinoctial_round_trip() {
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 };
+        let anomaly = Anomaly::from_true(&orbit, 1.2, 42);
+        let elements = orbit.to_equinoctial(&anomaly).unwrap();
+        let (back, back_anomaly) = Orbit::from_equinoctial(&elements, 42);
+        assert!((back.periapsis() - 7.0e6).abs() < 1e-6);
+        assert!((back.eccentricity() - 0.3).abs() < 1e-12);
+        assert!((back.inclination() - 0.9).abs() < 1e-12);
+        assert!((back.ascending_node() - 2.1).abs() < 1e-12);
+        assert!((back.angle_of_periapsis() - 0.7).abs() < 1e-12);
+        assert!((back_anomaly.true_anomaly - 1.2).abs() < 1e-9);
+        assert_eq!(back_anomaly.time_ms, 42);
+
+        // Circular and equatorial: the elements stay finite and the mean longitude carries the phase
+        let degenerate = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        let elements = degenerate.to_equinoctial(&Anomaly::from_true(&degenerate, 0.8, 0)).unwrap();
+        assert_eq!((elements.h, elements.k, elements.p, elements.q), (0.0, 0.0, 0.0, 0.0));
+        assert!((elements.lambda - 0.8).abs() < 1e-12);
+        let hyperbolic = Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 2.0 };
+        assert!(hyperbolic.to_equinoctial(&Anomaly::from_true(&hyperbolic, 0.0, 0)).is_none());
+    }
+
+    #[test]
+    fn equinoctial_propagation_closes_after_a_period() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.3, ascending_node : 0.0, inclination : 1e-10, periapsis : 7.0e6, eccentricity : 1e-7 };
+        let start = orbit.to_equinoctial(&Anomaly::from_true(&orbit, 2.0, 0)).unwrap();
+        let period = orbit.orbital_period(&body).unwrap();
+        let end = start.propagate_equinoctial(&body, period);
+        let wrapped = (end.lambda - start.lambda + std::f64::consts::PI).rem_euclid(2.0 * std::f64::consts::PI) - std::f64::consts::PI;
+        assert!(wrapped.abs() < 1e-12);
+        assert_eq!((end.a, end.h, end.k, end.p, end.q), (start.a, start.h, start.k, start.p, start.q));
+
+        let half = start.propagate_equinoctial(&body, period / 2.0);
+        let (classical, anomaly) = Orbit::from_equinoctial(&half, 0);
+        let expected = (anomaly.eccentric_anomaly + classical.ascending_node() + classical.angle_of_periapsis()).rem_euclid(2.0 * std::f64::consts::PI);
+        assert!((half.eccentric_longitude() - expected).abs() < 1e-9);
+
+        let eccentric = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 };
+        let anomaly = Anomaly::from_true(&eccentric, 1.2, 0);
+        let expected = (anomaly.eccentric_anomaly + 2.8).rem_euclid(2.0 * std::f64::consts::PI);
+        assert!((eccentric.to_equinoctial(&anomaly).unwrap().eccentric_longitude() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn eccentric_longitude_solves_near_parabolic_ellipses() {
+        for &e in [0.0, 0.5, 0.9, 0.95, 0.99, 0.995, 0.999].iter() {
+            for step in 0..32 {
+                let longitude_of_periapsis = 0.4f64;
+                let lambda = step as f64 * 2.0 * std::f64::consts::PI / 32.0 + 0.1;
+                let elements = EquinoctialElements { a : 7.0e6, h : e * longitude_of_periapsis.sin(), k : e * longitude_of_periapsis.cos(), p : 0.0, q : 0.0, lambda };
+                let f = elements.eccentric_longitude();
+                let residual = f + elements.h * f.cos() - elements.k * f.sin() - lambda;
+                let wrapped = (residual + std::f64::consts::PI).rem_euclid(2.0 * std::f64::consts::PI) - std::f64::consts::PI;
+                assert!(wrapped.abs() < 1e-9, "e {} λ {}: {}", e, lambda, wrapped);
+            }
+        }
+    }
+
+    #[test]
+    fn mee_round_trip_and_rates() {
+        let body = earth();
+        let orbits = [
+            Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 },
+            Orbit::Hyperbolic { angle_of_periapsis : 4.0, ascending_node : 0.5, inclination : 2.0, periapsis : 7.0e6, eccentricity : 1.4 }
+        ];
+        for orbit in orbits.iter() {
+            let anomaly = Anomaly::from_true(orbit, 1.2, 42);
+            let elements = orbit.to_mee(&anomaly);
+            let (back, back_anomaly) = Orbit::from_mee(&elements, 42);
+            assert!((back.periapsis() - orbit.periapsis()).abs() < 1e-6);
+            assert!((back.eccentricity() - orbit.eccentricity()).abs() < 1e-12);
+            assert!((back.inclination() - orbit.inclination()).abs() < 1e-12);
+            assert!((back.ascending_node() - orbit.ascending_node()).abs() < 1e-12);
+            assert!((back.angle_of_periapsis() - orbit.angle_of_periapsis()).abs() < 1e-12);
+            assert!((back_anomaly.true_anomaly - 1.2).abs() < 1e-9);
+
+            // Unperturbed, only the true longitude moves, at the two-body rate
+            let coasting = elements.mee_rates(&body, Vector3::zero());
+            assert_eq!(&coasting[..5], &[0.0; 5]);
+            assert!((coasting[5] - orbit.true_anomaly_rate(&body, &anomaly)).abs() < 1e-15);
+
+            // A small impulse changes the elements as the rates predict
+            let state = orbit.state_vector(&body, &anomaly);
+            let radial = state.position().unit();
+            let normal = state.position().cross(state.velocity()).unit();
+            let transverse = normal.cross(radial);
+            let accel = Vector3::new(0.2, -0.3, 0.4);
+            let dt = 1e-2;
+            let kicked = StateVector::new(state.position(), state.velocity() + (radial * accel.x + transverse * accel.y + normal * accel.z) * dt, 42);
+            let (after, after_anomaly) = Orbit::from_burnout(&kicked, &body).unwrap();
+            let moved = after.to_mee(&after_anomaly);
+            let rates = elements.mee_rates(&body, accel);
+            let changes = [moved.p - elements.p, moved.f - elements.f, moved.g - elements.g, moved.h - elements.h, moved.k - elements.k];
+            for (j, change) in changes.iter().enumerate() {
+                let scale = if j == 0 { elements.p } else { 1.0 };
+                assert!((change / dt - rates[j]).abs() < 1e-4 * rates[j].abs() + 1e-12 * scale, "{}: {} vs {}", j, change / dt, rates[j]);
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+
+/// Why an orbit could not be read or computed
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone)]
+pub enum OrbitError {
+    /// A required keyword was absent
+    MissingField(String),
+    /// A keyword's value could not be understood
+    InvalidValue { keyword : String, value : String },
+    /// An iterative solver ran out of iterations before meeting its tolerance
+    NotConverged { iterations : usize },
+    /// A maneuver or target can't be reached from the orbit, for the reason given
+    Unreachable(String)
+}
+
+impl fmt::Display for OrbitError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrbitError::MissingField(keyword) => write!(f, "missing {}", keyword),
+            OrbitError::InvalidValue { keyword, value } => write!(f, "invalid {} \"{}\"", keyword, value),
+            OrbitError::NotConverged { iterations } => write!(f, "did not converge within {} iterations", iterations),
+            OrbitError::Unreachable(reason) => write!(f, "unreachable: {}", reason)
+        }
+    }
+}
+
+impl std::error::Error for OrbitError { }
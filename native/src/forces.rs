@@ -0,0 +1,126 @@
+//! Perturbing accelerations (meters/second^2) for numerical integration. Positions are meters,
+//! relative to the central body's center, in an inertial frame.
+use crate::{ Body, Vector3 };
+
+/// Meters
+pub const ASTRONOMICAL_UNIT : f64 = 1.495_978_707e11;
+
+/// Acceleration on a satellite from a third body of gravitational parameter `mu_third` (m^3/s^2).
+/// Includes the indirect term: the central body is itself pulled toward the third body, so only the
+/// difference between the pull on the satellite and the pull on the central body remains.
+pub fn third_body_acceleration(satellite : Vector3, third_body : Vector3, mu_third : f64) -> Vector3 {
+    let to_third = third_body - satellite;
+    let direct = to_third * (1.0 / to_third.magnitude().powi(3));
+    let indirect = third_body * (1.0 / third_body.magnitude().powi(3));
+    (direct - indirect) * mu_third
+}
+
+/// Cannonball solar radiation pressure on a satellite, pushing directly away from the Sun.
+/// `area_to_mass` in m^2/kg, `reflectivity` the coefficient C_R (1 absorbing, 2 mirror),
+/// `solar_flux` in W/m^2 at one astronomical unit, and `c` the speed of light in m/s; the flux is
+/// scaled by the inverse square of the satellite's distance from the Sun. Shadow is not considered,
+/// see `in_shadow`.
+pub fn srp_acceleration(satellite : Vector3, sun : Vector3, area_to_mass : f64, reflectivity : f64, solar_flux : f64, c : f64) -> Vector3 {
+    let from_sun = satellite - sun;
+    let distance = from_sun.magnitude();
+    let pressure = solar_flux / c * (ASTRONOMICAL_UNIT / distance).powi(2);
+    from_sun * (pressure * reflectivity * area_to_mass / distance)
+}
+
+/// Acceleration from the zonal harmonics J2, J3 and J4 of `body`'s gravity field, each left out
+/// when None, on top of the point-mass pull. The body's pole is the z axis. J2 is the oblateness,
+/// pulling toward the equator; J3 is the odd pear-shaped term, different above and below it.
+pub fn zonal_acceleration(position : Vector3, body : &Body) -> Vector3 {
+    let r = position.magnitude();
+    let (x, y, z) = (position.x, position.y, position.z);
+    let s = z / r;
+    let mu_over_r2 = body.k() / (r * r);
+    let mut acceleration = Vector3::zero();
+    if let Some(j2) = body.j2 {
+        let scale = -1.5 * j2 * mu_over_r2 * (body.radius / r).powi(2) / r;
+        let planar = 1.0 - 5.0 * s * s;
+        acceleration = acceleration + Vector3::new(x * planar, y * planar, z * (3.0 - 5.0 * s * s)) * scale;
+    }
+    if let Some(j3) = body.j3 {
+        let scale = -2.5 * j3 * mu_over_r2 * (body.radius / r).powi(3) / r;
+        let planar = 3.0 * s - 7.0 * s.powi(3);
+        acceleration = acceleration + Vector3::new(x * planar, y * planar, r * (6.0 * s * s - 7.0 * s.powi(4) - 0.6)) * scale;
+    }
+    if let Some(j4) = body.j4 {
+        let scale = 1.875 * j4 * mu_over_r2 * (body.radius / r).powi(4) / r;
+        let planar = 1.0 - 14.0 * s * s + 21.0 * s.powi(4);
+        acceleration = acceleration + Vector3::new(x * planar, y * planar, z * (5.0 - 70.0 / 3.0 * s * s + 21.0 * s.powi(4))) * scale;
+    }
+    acceleration
+}
+
+/// Whether the satellite is inside the central body's cylindrical shadow, the body being a sphere of
+/// `body_radius` meters and the Sun's rays parallel
+pub fn in_shadow(satellite : Vector3, sun : Vector3, body_radius : f64) -> bool {
+    let sunward = sun.unit();
+    let along = satellite.dot(sunward);
+    along < 0.0 && (satellite - sunward * along).magnitude() < body_radius
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn third_body_tide_stretches_along_the_line() {
+        let moon = Vector3::new(3.844e8, 0.0, 0.0);
+        let mu_moon = 4.9028e12;
+        let near = third_body_acceleration(Vector3::new(7.0e6, 0.0, 0.0), moon, mu_moon);
+        let far = third_body_acceleration(Vector3::new(-7.0e6, 0.0, 0.0), moon, mu_moon);
+        let at_center = third_body_acceleration(Vector3::zero(), moon, mu_moon);
+        // Tidal: toward the moon on the near side, away on the far side, nothing at the center
+        assert!(near.x > 0.0);
+        assert!(far.x < 0.0);
+        assert!(at_center.magnitude() < 1e-18);
+        // Leading order 2μr/d³
+        assert!((near.x / (2.0 * mu_moon * 7.0e6 / 3.844e8f64.powi(3)) - 1.0).abs() < 0.03);
+    }
+
+    #[test]
+    fn srp_points_away_from_the_sun() {
+        let sun = Vector3::new(ASTRONOMICAL_UNIT, 0.0, 0.0);
+        let satellite = Vector3::new(0.0, 4.2e7, 0.0);
+        let acceleration = srp_acceleration(satellite, sun, 0.02, 1.3, 1361.0, 299_792_458.0);
+        let expected = 1361.0 / 299_792_458.0 * 1.3 * 0.02;
+        assert!((acceleration.magnitude() - expected).abs() < 1e-3 * expected);
+        assert!(acceleration.dot(sun - satellite) < 0.0);
+
+        assert!(in_shadow(Vector3::new(-7.0e6, 0.0, 0.0), sun, 6.371e6));
+        assert!(!in_shadow(Vector3::new(7.0e6, 0.0, 0.0), sun, 6.371e6));
+        assert!(!in_shadow(satellite, sun, 6.371e6));
+    }
+
+    #[test]
+    fn zonal_acceleration_is_the_gradient_of_the_potential() {
+        let body = Body::builder().mass(5.972e24).radius(6.378_137e6).j2(1.082_63e-3).j3(-2.532_66e-6).j4(-1.619_62e-6).build().unwrap();
+        // Perturbing potential -μ/r Σ Jn (R/r)^n Pn(z/r)
+        let potential = |p : Vector3| {
+            let (r, s) = (p.magnitude(), p.z / p.magnitude());
+            let legendre = [(3.0 * s * s - 1.0) / 2.0, (5.0 * s.powi(3) - 3.0 * s) / 2.0, (35.0 * s.powi(4) - 30.0 * s * s + 3.0) / 8.0];
+            let terms = [body.j2, body.j3, body.j4];
+            -body.k() / r * (0..3).map(|n| terms[n].unwrap_or(0.0) * (body.radius / r).powi(n as i32 + 2) * legendre[n]).sum::<f64>()
+        };
+        let position = Vector3::new(4.0e6, -3.0e6, 5.0e6);
+        let h = 1.0;
+        let gradient = Vector3::new(
+            potential(position + Vector3::i_hat() * h) - potential(position - Vector3::i_hat() * h),
+            potential(position + Vector3::j_hat() * h) - potential(position - Vector3::j_hat() * h),
+            potential(position + Vector3::k_hat() * h) - potential(position - Vector3::k_hat() * h)
+        ) * (0.5 / h);
+        let acceleration = zonal_acceleration(position, &body);
+        assert!((acceleration - gradient).magnitude() < 1e-6 * acceleration.magnitude(), "{:?} vs {:?}", acceleration, gradient);
+
+        // J3 alone pushes the two hemispheres differently
+        let odd = Body { j2 : None, j4 : None, ..body };
+        let north = zonal_acceleration(Vector3::new(5.0e6, 0.0, 4.0e6), &odd);
+        let south = zonal_acceleration(Vector3::new(5.0e6, 0.0, -4.0e6), &odd);
+        assert!((north.x - south.x).abs() > 1e-3 * north.magnitude());
+        let none = Body { j3 : None, ..odd };
+        assert_eq!(zonal_acceleration(position, &none).magnitude(), 0.0);
+    }
+}
@@ -0,0 +1,134 @@
+//! Moving orbits between inertial reference frames
+use affine_transforms::matrices::{ AffineMatrix };
+use affine_transforms::vector3::{ Vec3, Vector3 as AffineVector };
+use crate::{ Anomaly, Body, Orbit, Vector3 };
+
+/// Applies only the linear part of an affine transform, so any translation is ignored
+fn rotate(rotation : &AffineMatrix, v : Vector3) -> Vector3 {
+    let origin = rotation.apply_vec3(AffineVector::zero());
+    let moved = rotation.apply_vec3(AffineVector::new(v.x as f32, v.y as f32, v.z as f32));
+    Vector3::new((moved.x() - origin.x()) as f64, (moved.y() - origin.y()) as f64, (moved.z() - origin.z()) as f64)
+}
+
+impl Orbit {
+
+    /// This orbit expressed in another frame, `rotation` mapping vectors in the current frame to the
+    /// new one. Inclination, node, and angle of periapsis are recomputed from the rotated orbit normal
+    /// and periapsis direction; the shape (a, e) is carried over exactly. `AffineMatrix` is single
+    /// precision, so the angles are good to roughly 1e-7 radians.
+    pub fn rotated(&self, rotation : &AffineMatrix) -> Orbit {
+        let normal = rotate(rotation, self.normal()).unit();
+        let periapsis = rotate(rotation, self.perifocal_to_inertial(Vector3::i_hat()));
+        // Re-orthogonalize against the normal to shed single precision error
+        let periapsis = (periapsis - normal * periapsis.dot(normal)).unit();
+        self.with_axes(normal, periapsis)
+    }
+
+    /// This orbit, given in an Earth-equatorial frame, in the ecliptic frame sharing its x axis
+    /// (the vernal equinox). `obliquity` in radians, see `OBLIQUITY_J2000`.
+    pub fn to_ecliptic(&self, obliquity : f64) -> Orbit {
+        self.rotated_about_x(-obliquity)
+    }
+
+    /// This orbit, given in the ecliptic frame, in the Earth-equatorial frame sharing its x axis.
+    /// `obliquity` in radians, see `OBLIQUITY_J2000`.
+    pub fn to_equatorial(&self, obliquity : f64) -> Orbit {
+        self.rotated_about_x(obliquity)
+    }
+
+    /// `rotated` kept in double precision for rotations about the x axis
+    fn rotated_about_x(&self, angle : f64) -> Orbit {
+        let normal = self.normal().rotate_x(angle);
+        let periapsis = self.perifocal_to_inertial(Vector3::i_hat()).rotate_x(angle);
+        self.with_axes(normal, periapsis)
+    }
+
+    /// Model-to-inertial transforms at `steps` evenly spaced times over `duration` seconds from
+    /// `start`, both ends included, for drawing copies of an object along the orbit. Each places the
+    /// origin at the position (meters) with the model x axis along the velocity, z along the orbit
+    /// normal and y completing the right-handed set, toward the body for a closed orbit. Single
+    /// precision, so positions are good to about one part in 1e7.
+    pub fn sample_transforms(&self, body : &Body, start : &Anomaly, duration : f64, steps : usize) -> Vec<AffineMatrix> {
+        let interval = if steps > 1 { duration / (steps - 1) as f64 } else { 0.0 };
+        let z = self.normal();
+        (0..steps)
+            .map(|j| {
+                let state = self.state_vector(body, &self.next_anomaly(body, start, j as f64 * interval));
+                let (p, x) = (state.position(), state.velocity().unit());
+                let y = z.cross(x);
+                AffineMatrix::from_row_major(vec![
+                    x.x as f32, y.x as f32, z.x as f32, p.x as f32,
+                    x.y as f32, y.y as f32, z.y as f32, p.y as f32,
+                    x.z as f32, y.z as f32, z.z as f32, p.z as f32,
+                    0.0, 0.0, 0.0, 1.0
+                ])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ earth };
+
+    #[test]
+    fn rotation_round_trip() {
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 };
+        let rotation = || AffineMatrix::rotation_x(0.4).multiply(AffineMatrix::rotation_z(1.0));
+        let there = orbit.rotated(&rotation());
+        assert!((there.inclination() - orbit.inclination()).abs() > 0.01);
+        let back = there.rotated(&rotation().inverse());
+        assert_eq!(back.eccentricity(), orbit.eccentricity());
+        assert_eq!(back.periapsis(), orbit.periapsis());
+        assert!((back.inclination() - 0.9).abs() < 1e-5);
+        assert!((back.ascending_node() - 2.1).abs() < 1e-5);
+        assert!((back.angle_of_periapsis() - 0.7).abs() < 1e-5);
+    }
+
+    #[test]
+    fn ecliptic_round_trip() {
+        use crate::OBLIQUITY_J2000;
+        // An orbit in the ecliptic plane is inclined by the obliquity to the equator, at the equinox node
+        let ecliptic = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 1.496e11 };
+        let equatorial = ecliptic.to_equatorial(OBLIQUITY_J2000);
+        assert!((equatorial.inclination() - 23.4393f64.to_radians()).abs() < 1e-12);
+        assert!(equatorial.ascending_node().abs() < 1e-12);
+
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 };
+        let back = orbit.to_ecliptic(OBLIQUITY_J2000).to_equatorial(OBLIQUITY_J2000);
+        assert!((back.inclination() - 0.9).abs() < 1e-12);
+        assert!((back.ascending_node() - 2.1).abs() < 1e-12);
+        assert!((back.angle_of_periapsis() - 0.7).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rotation_about_the_pole_moves_the_node() {
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.3, ascending_node : 0.2, inclination : 0.5, semimajor_axis : 7.0e6 };
+        let turned = orbit.rotated(&AffineMatrix::rotation_z(0.5));
+        assert!((turned.ascending_node() - 0.7).abs() < 1e-6);
+        assert!((turned.inclination() - 0.5).abs() < 1e-6);
+        assert!((turned.angle_of_periapsis() - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sampled_transforms_follow_the_orbit() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 };
+        let start = Anomaly::from_true(&orbit, 0.4, 1_000);
+        let transforms = orbit.sample_transforms(&body, &start, 3000.0, 4);
+        assert_eq!(transforms.len(), 4);
+        for (j, transform) in transforms.iter().enumerate() {
+            let state = orbit.state_vector(&body, &orbit.next_anomaly(&body, &start, j as f64 * 1000.0));
+            let origin = transform.apply_vec3(AffineVector::zero());
+            let origin = Vector3::new(origin.x() as f64, origin.y() as f64, origin.z() as f64);
+            assert!((origin - state.position()).magnitude() < 2.0);
+            // Axes are the first three columns
+            let m = transform.as_row_major_vec();
+            let column = |c : usize| Vector3::new(m[c] as f64, m[4 + c] as f64, m[8 + c] as f64);
+            assert!((column(0) - state.velocity().unit()).magnitude() < 1e-6);
+            assert!((column(2) - orbit.normal()).magnitude() < 1e-6);
+            assert!(column(1).dot(state.position()) < 0.0);
+        }
+    }
+}
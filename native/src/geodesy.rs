@@ -0,0 +1,33 @@
+//! Positions on and above the body's surface
+use crate::{ Body, Vector3 };
+
+/// Body-fixed position in meters of a point at geodetic latitude and longitude (radians) and
+/// altitude (meters) over the body's ellipsoid
+pub fn geodetic_to_ecef(latitude : f64, longitude : f64, altitude : f64, body : &Body) -> Vector3 {
+    let e2 = body.flattening * (2.0 - body.flattening);
+    let (sin_lat, cos_lat) = latitude.sin_cos();
+    // Prime-vertical radius of curvature
+    let n = body.radius / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+    Vector3::new(
+        (n + altitude) * cos_lat * longitude.cos(),
+        (n + altitude) * cos_lat * longitude.sin(),
+        (n * (1.0 - e2) + altitude) * sin_lat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{ PI };
+
+    const WGS84_FLATTENING : f64 = 1.0 / 298.257_223_563;
+
+    #[test]
+    fn ellipsoid_axes() {
+        let earth = Body::builder().mass(5.972e24).radius(6.378_137e6).flattening(WGS84_FLATTENING).build().unwrap();
+        let equator = geodetic_to_ecef(0.0, PI / 2.0, 100.0, &earth);
+        assert!((equator - Vector3::new(0.0, earth.radius + 100.0, 0.0)).magnitude() < 1e-6);
+        let pole = geodetic_to_ecef(PI / 2.0, 0.0, 0.0, &earth);
+        assert!((pole.z - earth.radius * (1.0 - WGS84_FLATTENING)).abs() < 1e-6);
+        assert!(pole.x.abs() < 1e-6);
+    }
+}
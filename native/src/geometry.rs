@@ -0,0 +1,206 @@
+//! Shape of the conic in its own plane, independent of timing
+use std::f64::consts::{ PI };
+use crate::{ Orbit, Vector3 };
+
+/// Simpson intervals (per sample requested) used to tabulate arc length
+const ARC_LENGTH_RESOLUTION : usize = 64;
+
+impl Orbit {
+
+    /// Perifocal (x toward periapsis, y along motion at periapsis) coordinates in meters at the given
+    /// eccentric anomaly, with no Kepler solve. Hyperbolic orbits take the hyperbolic anomaly F and
+    /// parabolic orbits Barker's D, matching `Anomaly::eccentric_anomaly`.
+    pub fn position_from_eccentric(&self, eccentric_anomaly : f64) -> (f64, f64) {
+        match *self {
+            Orbit::Circular { semimajor_axis, .. } => (
+                semimajor_axis * eccentric_anomaly.cos(),
+                semimajor_axis * eccentric_anomaly.sin()
+            ),
+            Orbit::Elliptical { periapsis, eccentricity, .. } => {
+                let a = periapsis / (1.0 - eccentricity);
+                (a * (eccentric_anomaly.cos() - eccentricity), a * (1.0 - eccentricity * eccentricity).sqrt() * eccentric_anomaly.sin())
+            },
+            Orbit::Parabolic { periapsis, .. } => {
+                let d = eccentric_anomaly;
+                (periapsis * (1.0 - d * d), 2.0 * periapsis * d)
+            },
+            Orbit::Hyperbolic { periapsis, eccentricity, .. } => {
+                let a = periapsis / (eccentricity - 1.0);
+                (a * (eccentricity - eccentric_anomaly.cosh()), a * (eccentricity * eccentricity - 1.0).sqrt() * eccentric_anomaly.sinh())
+            }
+        }
+    }
+
+    /// (ascending, descending) true anomalies where the orbit crosses the plane through the focus with
+    /// the given normal, ascending meaning toward the side the normal points to. None when the orbit
+    /// lies in the plane. On open orbits a crossing may fall beyond the asymptotes and never be reached.
+    pub fn plane_intersection(&self, plane_normal : Vector3) -> Option<(f64, f64)> {
+        let along_periapsis = plane_normal.dot(self.perifocal_to_inertial(Vector3::i_hat()));
+        let along_motion = plane_normal.dot(self.perifocal_to_inertial(Vector3::j_hat()));
+        if along_periapsis.hypot(along_motion) < 1e-12 * plane_normal.magnitude() {
+            return None;
+        }
+        let ascending = (-along_periapsis).atan2(along_motion).rem_euclid(2.0 * PI);
+        Some((ascending, (ascending + PI).rem_euclid(2.0 * PI)))
+    }
+
+    /// (ascending, descending) true anomalies, `(π/2, -π/2)`, at the ends of the latus rectum: the chord
+    /// through the focus perpendicular to the line of apsides, where the radius is `parameter()`. Every
+    /// conic reaches both, open orbits before their asymptotes.
+    pub fn latus_rectum_points(&self) -> (f64, f64) {
+        (PI / 2.0, -PI / 2.0)
+    }
+
+    /// Square meters enclosed, `π·a·b`, None for open orbits
+    pub fn area(&self) -> Option<f64> {
+        match *self {
+            Orbit::Circular { .. } | Orbit::Elliptical { .. } => {
+                let (a, e) = (self.semimajor_axis()?, self.eccentricity());
+                Some(PI * a * a * (1.0 - e * e).sqrt())
+            },
+            Orbit::Parabolic { .. } | Orbit::Hyperbolic { .. } => None
+        }
+    }
+
+    /// Meters, the time-averaged distance from the focus `a·(1 + e²/2)`, None for open orbits. This
+    /// lies beyond the semi-major axis; the average over true anomaly would be `b`, short of it.
+    pub fn mean_radius(&self) -> Option<f64> {
+        self.area().map(|_| self.semimajor_axis().unwrap() * (1.0 + self.eccentricity().powi(2) / 2.0))
+    }
+
+    /// Whether the two orbits are the same conic up to orientation: semi-major axes within `tol`
+    /// relative to this orbit's, and eccentricities within `tol` absolute. Equal shape means equal
+    /// period and energy; the three orientation angles are ignored. Parabolas, which have no
+    /// semi-major axis, compare periapsis instead, and match only other parabolas.
+    pub fn same_shape(&self, other : &Orbit, tol : f64) -> bool {
+        let size = |orbit : &Orbit| orbit.semimajor_axis().unwrap_or_else(|| orbit.periapsis());
+        let is_parabolic = |orbit : &Orbit| matches!(*orbit, Orbit::Parabolic { .. });
+        is_parabolic(self) == is_parabolic(other)
+            && (size(other) - size(self)).abs() <= tol * size(self).abs()
+            && (other.eccentricity() - self.eccentricity()).abs() <= tol
+    }
+
+    /// `n` perifocal points, starting at periapsis, spaced by equal arc length rather than equal angle.
+    /// Arc length is tabulated against eccentric anomaly, where `ds/dE = a·sqrt(1 - e²cos²E)`, and
+    /// inverted by interpolation. Empty for open orbits, which have no finite circumference.
+    pub fn sample_arc_length(&self, n : usize) -> Vec<(f64, f64)> {
+        let (a, e) = match *self {
+            Orbit::Circular { .. } | Orbit::Elliptical { .. } if n > 0 => (self.semimajor_axis().unwrap(), self.eccentricity()),
+            _ => return Vec::new()
+        };
+
+        let intervals = 2 * ARC_LENGTH_RESOLUTION * n;
+        let step = 2.0 * PI / intervals as f64;
+        let speed = |eccentric : f64| a * (1.0 - e * e * eccentric.cos().powi(2)).sqrt();
+        // Cumulative length at every second node, accumulated one Simpson panel at a time
+        let mut lengths = vec![0.0];
+        for panel in 0..intervals / 2 {
+            let start = 2.0 * panel as f64 * step;
+            let area = step / 3.0 * (speed(start) + 4.0 * speed(start + step) + speed(start + 2.0 * step));
+            lengths.push(lengths[panel] + area);
+        }
+        let circumference = lengths[lengths.len() - 1];
+
+        let mut points = Vec::with_capacity(n);
+        let mut panel = 0;
+        for sample in 0..n {
+            let target = circumference * sample as f64 / n as f64;
+            while lengths[panel + 1] < target {
+                panel += 1;
+            }
+            let fraction = (target - lengths[panel]) / (lengths[panel + 1] - lengths[panel]);
+            points.push(self.position_from_eccentric((2.0 * panel as f64 + 2.0 * fraction) * step));
+        }
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ earth, Anomaly, Orbit, Vector3 };
+    use std::f64::consts::{ PI };
+
+    #[test]
+    fn latus_rectum_is_at_the_parameter() {
+        let orbit = Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 1.2 };
+        let (ascending, descending) = orbit.latus_rectum_points();
+        for &nu in [ascending, descending].iter() {
+            assert!((orbit.distance_from_parent(&Anomaly::from_true(&orbit, nu, 0)) - orbit.parameter()).abs() < 1e-6);
+        }
+        assert_eq!(ascending, -descending);
+    }
+
+    #[test]
+    fn area_over_areal_velocity_is_the_period() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.5 };
+        let period = orbit.area().unwrap() / orbit.areal_velocity(&body);
+        assert!((period / orbit.orbital_period(&body).unwrap() - 1.0).abs() < 1e-12);
+        let mean = orbit.orbit_averaged(&body, |orbit, anomaly| orbit.distance_from_parent(anomaly), 256);
+        assert!((orbit.mean_radius().unwrap() / mean - 1.0).abs() < 1e-12);
+        let parabolic = Orbit::Parabolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6 };
+        assert!(parabolic.area().is_none() && parabolic.mean_radius().is_none());
+    }
+
+    #[test]
+    fn eccentric_parametrization_matches_true_anomaly() {
+        let orbits = [
+            Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.4 },
+            Orbit::Parabolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6 },
+            Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 1.8 },
+        ];
+        for orbit in orbits.iter() {
+            let true_anomaly = 1.2;
+            let (eccentric, _) = orbit.anomalies_from_true(true_anomaly);
+            let (x, y) = orbit.position_from_eccentric(eccentric);
+            let r = orbit.distance_from_parent(&Anomaly { time_ms : 0, true_anomaly, mean_anomaly : 0.0, eccentric_anomaly : eccentric });
+            assert!((x - r * true_anomaly.cos()).abs() < 1e-3);
+            assert!((y - r * true_anomaly.sin()).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn equatorial_plane_crossings_are_the_nodes() {
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 1.0, ascending_node : 0.4, inclination : 0.3, periapsis : 7.0e6, eccentricity : 0.2 };
+        let (ascending, descending) = orbit.plane_intersection(Vector3::k_hat()).unwrap();
+        assert!((ascending - (2.0 * PI - 1.0)).abs() < 1e-12);
+        assert!((descending - (PI - 1.0)).abs() < 1e-12);
+
+        let equatorial = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        assert!(equatorial.plane_intersection(Vector3::k_hat()).is_none());
+    }
+
+    #[test]
+    fn arc_length_samples_are_evenly_spaced() {
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.7 };
+        let points = orbit.sample_arc_length(40);
+        assert_eq!(points.len(), 40);
+        let chords : Vec<f64> = (0..points.len())
+            .map(|i| {
+                let (x0, y0) = points[i];
+                let (x1, y1) = points[(i + 1) % points.len()];
+                (x1 - x0).hypot(y1 - y0)
+            })
+            .collect();
+        let shortest = chords.iter().cloned().fold(f64::INFINITY, f64::min);
+        let longest = chords.iter().cloned().fold(0.0, f64::max);
+        // Chords of equal arcs differ only by the local curvature
+        assert!((longest - shortest) / longest < 0.01);
+
+        let escape = Orbit::Parabolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6 };
+        assert!(escape.sample_arc_length(10).is_empty());
+    }
+
+    #[test]
+    fn same_shape_ignores_orientation() {
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.1, ascending_node : 0.2, inclination : 0.3, periapsis : 7.0e6, eccentricity : 0.1 };
+        let rotated = Orbit::Elliptical { angle_of_periapsis : 2.0, ascending_node : 4.0, inclination : 1.5, periapsis : 7.0e6 * (1.0 + 1e-7), eccentricity : 0.1 };
+        assert!(orbit.same_shape(&rotated, 1e-6));
+        assert!(!orbit.same_shape(&rotated, 1e-8));
+        let rounder = Orbit::Elliptical { angle_of_periapsis : 0.1, ascending_node : 0.2, inclination : 0.3, periapsis : 7.0e6, eccentricity : 0.101 };
+        assert!(!orbit.same_shape(&rounder, 1e-6));
+        let circle = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        let parabola = Orbit::Parabolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6 };
+        assert!(!circle.same_shape(&parabola, 1.0));
+    }
+}
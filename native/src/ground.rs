@@ -0,0 +1,96 @@
+//! The path traced over the surface of a rotating body
+use crate::{ Anomaly, Body, Orbit };
+
+impl Orbit {
+
+    /// Geocentric (latitude, longitude) in radians under the object at `samples + 1` evenly spaced
+    /// times over one revolution from `start`, both endpoints included. Longitude is body-fixed,
+    /// with the body's prime meridian on the inertial x axis at `start`, so successive samples
+    /// drift west by the body's rotation in between. Empty for open orbits.
+    pub fn ground_track(&self, body : &Body, start : &Anomaly, samples : usize) -> Vec<(f64, f64)> {
+        let period = match self.orbital_period(body) {
+            Some(period) if samples > 0 => period,
+            _ => return Vec::new()
+        };
+        (0..=samples)
+            .map(|j| {
+                let dt = period * j as f64 / samples as f64;
+                let anomaly = self.next_anomaly(body, start, dt);
+                let position = self.state_vector(body, &anomaly).position().rotate_z(-body.rotation_rate() * dt);
+                ((position.z / position.magnitude()).asin(), position.y.atan2(position.x))
+            })
+            .collect()
+    }
+
+    /// Meters travelled over the surface of a sphere of the body's equatorial radius in one
+    /// revolution, from periapsis, summing great-circle distances between `samples` successive
+    /// ground track points. Includes the body turning beneath the orbit. NaN for open orbits.
+    pub fn ground_track_length(&self, body : &Body, samples : usize) -> f64 {
+        let track = self.ground_track(body, &Anomaly::from_true(self, 0.0, 0), samples);
+        if track.is_empty() {
+            return f64::NAN;
+        }
+        track.windows(2)
+            .map(|pair| {
+                let ((lat1, lon1), (lat2, lon2)) = (pair[0], pair[1]);
+                // Haversine, well conditioned for the short steps between samples
+                let a = ((lat2 - lat1) / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+                2.0 * body.radius * a.sqrt().min(1.0).asin()
+            })
+            .sum()
+    }
+
+    /// Meters of ground, measured along the surface of a sphere of the body's equatorial radius,
+    /// seen across track by a nadir-pointing sensor of half field of view `half_fov` (radians) at
+    /// `altitude` meters. Curvature widens the swath over the flat-ground `2h·tan(half_fov)`, and a
+    /// field of view reaching past the horizon is limited to it.
+    pub fn swath_width(&self, body : &Body, altitude : f64, half_fov : f64) -> f64 {
+        let radius = body.radius;
+        // Half-angle the body subtends from the sensor
+        let horizon = (radius / (radius + altitude)).asin();
+        let nadir = half_fov.min(horizon);
+        // Elevation of the sensor as seen from the edge of the swath
+        let elevation = (nadir.sin() / horizon.sin()).min(1.0).acos();
+        2.0 * radius * (std::f64::consts::FRAC_PI_2 - nadir - elevation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{ PI };
+    use crate::{ earth, Body, Orbit };
+
+    #[test]
+    fn ground_track_length_includes_rotation() {
+        let still = earth();
+        let turning = Body { rotation_period : Some(86_164.1), ..still };
+        let polar = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : PI / 2.0, semimajor_axis : 7.0e6 };
+        // Without rotation a circular orbit traces a great circle
+        assert!((polar.ground_track_length(&still, 1000) / (2.0 * PI * still.radius) - 1.0).abs() < 1e-6);
+        // Equatorial, prograde: the ground moves with the satellite and the track shortens by the
+        // ground's own travel in one period
+        let equatorial = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        let period = equatorial.orbital_period(&turning).unwrap();
+        let expected = 2.0 * PI * turning.radius * (1.0 - period / 86_164.1);
+        assert!((equatorial.ground_track_length(&turning, 1000) / expected - 1.0).abs() < 1e-6);
+        assert!(polar.ground_track_length(&turning, 1000) > 2.0 * PI * still.radius);
+    }
+
+    #[test]
+    fn swath_width_on_a_sphere() {
+        let body = earth();
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 1.0, semimajor_axis : 7.071e6 };
+        // Narrow fields of view approach the flat-ground width
+        let narrow = orbit.swath_width(&body, 700.0e3, 1e-3);
+        assert!((narrow / (2.0 * 700.0e3 * 1e-3f64.tan()) - 1.0).abs() < 1e-6);
+        // Law of sines for the edge of the swath: the central angle λ and nadir angle η satisfy
+        // R sin(η + λ) = (R + h) sin η
+        let width = orbit.swath_width(&body, 700.0e3, 0.5);
+        let central = width / (2.0 * body.radius);
+        assert!((body.radius * (0.5 + central).sin() - 7.071e6 * 0.5f64.sin()).abs() < 1e-3);
+        assert!(width > 2.0 * 700.0e3 * 0.5f64.tan());
+        // Past the horizon the swath stops at the limb
+        let limb = 2.0 * body.radius * (body.radius / 7.071e6).acos();
+        assert!((orbit.swath_width(&body, 700.0e3, 1.5) - limb).abs() < 1e-3);
+    }
+}
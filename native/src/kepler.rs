@@ -0,0 +1,209 @@
+//! Conversions between true, eccentric, and mean anomaly for each conic section.
+//! For hyperbolic orbits the "eccentric" anomaly is the hyperbolic anomaly F, and for
+//! parabolic orbits it is Barker's D = tan(ν/2).
+
+use std::f64::consts::{ PI };
+use crate::{ OrbitError };
+use crate::solvers::{ KeplerSolver, NewtonSolver, SolverConfig, MAX_ITERATIONS };
+
+pub(crate) const TOLERANCE : f64 = 1e-12;
+
+/// Eccentric anomaly of an ellipse of eccentricity `e` from the true anomaly, radians, on the same
+/// turn as `true_anomaly` when that lies in `(-π, π]`
+pub fn true_to_eccentric(e : f64, true_anomaly : f64) -> f64 {
+    let half = true_anomaly / 2.0;
+    2.0 * ((1.0 - e).sqrt() * half.sin()).atan2((1.0 + e).sqrt() * half.cos())
+}
+
+/// Mean anomaly of an ellipse from the eccentric anomaly, Kepler's equation `M = E - e sin E`
+pub fn eccentric_to_mean(e : f64, eccentric_anomaly : f64) -> f64 {
+    eccentric_anomaly - e * eccentric_anomaly.sin()
+}
+
+/// Eccentric anomaly of an ellipse from the mean anomaly, inverting Kepler's equation by
+/// Newton-Raphson to within 1e-12 radians
+pub fn mean_to_eccentric(e : f64, mean_anomaly : f64) -> f64 {
+    NewtonSolver::solve(e, mean_anomaly, TOLERANCE)
+}
+
+/// True anomaly of an ellipse from the eccentric anomaly, radians
+pub fn eccentric_to_true(e : f64, eccentric_anomaly : f64) -> f64 {
+    let half = eccentric_anomaly / 2.0;
+    2.0 * ((1.0 + e).sqrt() * half.sin()).atan2((1.0 - e).sqrt() * half.cos())
+}
+
+/// (eccentric, mean) anomaly of an elliptical orbit from the true anomaly
+pub(crate) fn elliptical_from_true(e : f64, true_anomaly : f64) -> (f64, f64) {
+    let eccentric = true_to_eccentric(e, true_anomaly);
+    (eccentric, eccentric_to_mean(e, eccentric))
+}
+
+/// (true, eccentric) anomaly of an elliptical orbit from the mean anomaly, by Newton-Raphson
+pub(crate) fn elliptical_from_mean(e : f64, mean_anomaly : f64) -> (f64, f64) {
+    let eccentric = mean_to_eccentric(e, mean_anomaly);
+    (eccentric_to_true(e, eccentric), eccentric)
+}
+
+/// (true, mean) anomaly of an elliptical orbit from the eccentric anomaly
+pub(crate) fn elliptical_from_eccentric(e : f64, eccentric_anomaly : f64) -> (f64, f64) {
+    (eccentric_to_true(e, eccentric_anomaly), eccentric_to_mean(e, eccentric_anomaly))
+}
+
+/// Hyperbolic anomaly F of a hyperbola of eccentricity `e` from the true anomaly, radians.
+/// `OrbitError::InvalidValue` unless `|ν|`, taken in `(-π, π]`, is short of the asymptote
+/// `acos(-1/e)`, beyond which the hyperbola doesn't reach.
+pub fn hyperbolic_true_to_anomaly(e : f64, true_anomaly : f64) -> Result<f64, OrbitError> {
+    let wrapped = if true_anomaly.abs() > PI { (true_anomaly + PI).rem_euclid(2.0 * PI) - PI } else { true_anomaly };
+    if wrapped.is_nan() || wrapped.abs() >= (-1.0 / e).acos() {
+        return Err(OrbitError::InvalidValue { keyword : "true anomaly".to_string(), value : true_anomaly.to_string() });
+    }
+    Ok(2.0 * (((e - 1.0) / (e + 1.0)).sqrt() * (wrapped / 2.0).tan()).atanh())
+}
+
+/// Mean anomaly of a hyperbola from the hyperbolic anomaly, `M = e sinh F - F`
+pub fn hyperbolic_anomaly_to_mean(e : f64, hyperbolic_anomaly : f64) -> f64 {
+    e * hyperbolic_anomaly.sinh() - hyperbolic_anomaly
+}
+
+/// Hyperbolic anomaly from the mean anomaly, inverting `M = e sinh F - F` by Newton-Raphson to the
+/// default `SolverConfig`, or `OrbitError::NotConverged` rather than a partly converged estimate
+pub fn mean_to_hyperbolic_anomaly(e : f64, mean_anomaly : f64) -> Result<f64, OrbitError> {
+    let config = SolverConfig::default();
+    solve_hyperbolic(e, mean_anomaly, &config).map_err(|_| OrbitError::NotConverged { iterations : config.max_iterations })
+}
+
+/// True anomaly of a hyperbola from the hyperbolic anomaly, radians, inside the asymptotes
+pub fn hyperbolic_anomaly_to_true(e : f64, hyperbolic_anomaly : f64) -> f64 {
+    2.0 * (((e + 1.0) / (e - 1.0)).sqrt() * (hyperbolic_anomaly / 2.0).tanh()).atan()
+}
+
+/// Barker's `D = tan(ν/2)` of a parabola from the true anomaly. `OrbitError::InvalidValue` at
+/// `ν = π`, taken in `(-π, π]`, which the parabola only reaches at infinity.
+pub fn parabolic_true_to_barker(true_anomaly : f64) -> Result<f64, OrbitError> {
+    let wrapped = (true_anomaly + PI).rem_euclid(2.0 * PI) - PI;
+    if wrapped.is_nan() || wrapped.abs() >= PI {
+        return Err(OrbitError::InvalidValue { keyword : "true anomaly".to_string(), value : true_anomaly.to_string() });
+    }
+    Ok((wrapped / 2.0).tan())
+}
+
+/// Barker's mean anomaly `D + D³/3` of a parabola
+pub fn barker_to_mean(d : f64) -> f64 {
+    d + d * d * d / 3.0
+}
+
+/// Barker's D from the mean anomaly, solving Barker's cubic directly. D is odd in M, so the cubic
+/// is solved for |M|, where the root's two terms add rather than cancel, and the sign put back.
+pub fn mean_to_barker(mean_anomaly : f64) -> f64 {
+    let magnitude = mean_anomaly.abs();
+    let a = (1.5 * magnitude + (2.25 * magnitude * magnitude + 1.0).sqrt()).cbrt();
+    (a - 1.0 / a).copysign(mean_anomaly)
+}
+
+/// True anomaly of a parabola from Barker's D, radians in `(-π, π)`
+pub fn barker_to_true(d : f64) -> f64 {
+    2.0 * d.atan()
+}
+
+/// (hyperbolic, mean) anomaly of a hyperbolic orbit from the true anomaly, NaN beyond the asymptotes
+pub(crate) fn hyperbolic_from_true(e : f64, true_anomaly : f64) -> (f64, f64) {
+    let hyperbolic = hyperbolic_true_to_anomaly(e, true_anomaly).unwrap_or(f64::NAN);
+    (hyperbolic, hyperbolic_anomaly_to_mean(e, hyperbolic))
+}
+
+/// (true, hyperbolic) anomaly of a hyperbolic orbit from the mean anomaly, by Newton-Raphson. Keeps
+/// the last estimate if the iterations run out; callers that need to know use `Anomaly::try_from_mean`
+/// or `Orbit::try_next_anomaly`, which fail with `OrbitError::NotConverged`.
+pub(crate) fn hyperbolic_from_mean(e : f64, mean_anomaly : f64) -> (f64, f64) {
+    let config = SolverConfig { tolerance : TOLERANCE, max_iterations : MAX_ITERATIONS };
+    let hyperbolic = solve_hyperbolic(e, mean_anomaly, &config).unwrap_or_else(|last| last);
+    (hyperbolic_anomaly_to_true(e, hyperbolic), hyperbolic)
+}
+
+/// Hyperbolic anomaly by Newton-Raphson on `M = e sinh F - F`, converged once the correction is
+/// under the tolerance relative to `max(|F|, 1)`; Err holds the last estimate if the budget ran out
+pub(crate) fn solve_hyperbolic(e : f64, mean_anomaly : f64, config : &SolverConfig) -> Result<f64, f64> {
+    let mut hyperbolic = (2.0 * mean_anomaly / e).asinh();
+    for _ in 0..config.max_iterations {
+        let step = (e * hyperbolic.sinh() - hyperbolic - mean_anomaly) / (e * hyperbolic.cosh() - 1.0);
+        hyperbolic -= step;
+        if step.abs() < config.tolerance * hyperbolic.abs().max(1.0) {
+            return Ok(hyperbolic);
+        }
+    }
+    Err(hyperbolic)
+}
+
+/// (true, mean) anomaly of a hyperbolic orbit from the hyperbolic anomaly
+pub(crate) fn hyperbolic_from_hyperbolic(e : f64, hyperbolic_anomaly : f64) -> (f64, f64) {
+    (hyperbolic_anomaly_to_true(e, hyperbolic_anomaly), hyperbolic_anomaly_to_mean(e, hyperbolic_anomaly))
+}
+
+/// (Barker's D, mean) anomaly of a parabolic orbit from the true anomaly
+pub(crate) fn parabolic_from_true(true_anomaly : f64) -> (f64, f64) {
+    let d = (true_anomaly / 2.0).tan();
+    (d, barker_to_mean(d))
+}
+
+/// (true, Barker's D) anomaly of a parabolic orbit from the mean anomaly, solving Barker's cubic directly
+pub(crate) fn parabolic_from_mean(mean_anomaly : f64) -> (f64, f64) {
+    let d = mean_to_barker(mean_anomaly);
+    (barker_to_true(d), d)
+}
+
+/// (true, mean) anomaly of a parabolic orbit from Barker's D
+pub(crate) fn parabolic_from_barker(d : f64) -> (f64, f64) {
+    (barker_to_true(d), barker_to_mean(d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elliptical_conversions_round_trip() {
+        for &e in [0.0, 0.1, 0.5, 0.9, 0.99].iter() {
+            for k in -7..=7 {
+                let true_anomaly = k as f64 * 0.44;
+                let eccentric = true_to_eccentric(e, true_anomaly);
+                let mean = eccentric_to_mean(e, eccentric);
+                let back = eccentric_to_true(e, mean_to_eccentric(e, mean));
+                assert!((back - true_anomaly).abs() < 1e-9, "e {} ν {}: {}", e, true_anomaly, back);
+                // Kepler's equation holds for the recovered eccentric anomaly
+                assert!((eccentric_to_mean(e, mean_to_eccentric(e, mean)) - mean).abs() < 1e-12);
+            }
+        }
+        // Apsides map to themselves
+        assert_eq!(true_to_eccentric(0.3, 0.0), 0.0);
+        assert!((eccentric_to_true(0.3, PI) - PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn open_conversions_round_trip() {
+        for &e in [1.01f64, 1.5, 3.0].iter() {
+            let asymptote = (-1.0 / e).acos();
+            for k in -9..=9 {
+                let true_anomaly = k as f64 / 10.0 * asymptote;
+                let hyperbolic = hyperbolic_true_to_anomaly(e, true_anomaly).unwrap();
+                let mean = hyperbolic_anomaly_to_mean(e, hyperbolic);
+                let back = hyperbolic_anomaly_to_true(e, mean_to_hyperbolic_anomaly(e, mean).unwrap());
+                assert!((back - true_anomaly).abs() < 1e-9, "e {} ν {}: {}", e, true_anomaly, back);
+            }
+            assert!(hyperbolic_true_to_anomaly(e, asymptote).is_err());
+            assert!(hyperbolic_true_to_anomaly(e, -1.01 * asymptote).is_err());
+            assert!(matches!(mean_to_hyperbolic_anomaly(e, f64::NAN), Err(OrbitError::NotConverged { .. })));
+        }
+        for k in -9..=9 {
+            let true_anomaly = k as f64 * 0.34;
+            let mean = barker_to_mean(parabolic_true_to_barker(true_anomaly).unwrap());
+            assert!((barker_to_true(mean_to_barker(mean)) - true_anomaly).abs() < 1e-9);
+        }
+        assert!(parabolic_true_to_barker(PI).is_err());
+        // Far out on either leg, where the direct root used to cancel for negative M
+        for &mean in [-1e7, -1e4, -30.0, 30.0, 1e4, 1e7].iter() {
+            let d = mean_to_barker(mean);
+            assert!(((barker_to_mean(d) - mean) / mean).abs() < 1e-12, "M {}: {}", mean, barker_to_mean(d));
+            assert_eq!(d, -mean_to_barker(-mean));
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! A plain keyword = value listing of an orbit's elements, for logs and hand-edited files
+use std::fmt::{ Write };
+use crate::{ Orbit, OrbitError };
+use crate::ccsds::{ invalid, parse_kvn };
+
+impl Orbit {
+
+    /// The elements one per line as `KEYWORD = value [unit]`, in meters and radians, starting with
+    /// the variant's `ORBIT_TYPE`. Values are written in full so that `from_kvn` reads back exactly
+    /// the same orbit; circular orbits list their semi-major axis and the others their periapsis,
+    /// and parabolic orbits leave out the eccentricity.
+    pub fn to_kvn(&self) -> String {
+        let mut text = String::new();
+        let (orbit_type, size_keyword, size) = match *self {
+            Orbit::Circular { semimajor_axis, .. } => ("CIRCULAR", "SEMI_MAJOR_AXIS", semimajor_axis),
+            Orbit::Elliptical { periapsis, .. } => ("ELLIPTICAL", "PERIAPSIS", periapsis),
+            Orbit::Parabolic { periapsis, .. } => ("PARABOLIC", "PERIAPSIS", periapsis),
+            Orbit::Hyperbolic { periapsis, .. } => ("HYPERBOLIC", "PERIAPSIS", periapsis)
+        };
+        // Writing to a String can't fail
+        let _ = writeln!(text, "ORBIT_TYPE = {}", orbit_type);
+        let _ = writeln!(text, "{} = {} [m]", size_keyword, size);
+        if let Orbit::Elliptical { eccentricity, .. } | Orbit::Hyperbolic { eccentricity, .. } = *self {
+            let _ = writeln!(text, "ECCENTRICITY = {}", eccentricity);
+        }
+        let _ = writeln!(text, "INCLINATION = {} [rad]", self.inclination());
+        let _ = writeln!(text, "RA_OF_ASC_NODE = {} [rad]", self.ascending_node());
+        let _ = writeln!(text, "ARG_OF_PERICENTER = {} [rad]", self.angle_of_periapsis());
+        text
+    }
+
+    /// Reads the listing `to_kvn` writes. Whitespace around keywords and values, blank lines, and
+    /// lines starting with `#` or `COMMENT` are ignored, as are unit annotations. Fails on a missing
+    /// keyword, an unparseable number, or an eccentricity outside its variant's range.
+    pub fn from_kvn(text : &str) -> Result<Orbit, OrbitError> {
+        let uncommented : String = text.lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let fields = parse_kvn(&uncommented);
+        let field = |keyword : &str| fields.get(keyword).ok_or_else(|| OrbitError::MissingField(keyword.to_string()));
+        let number = |keyword : &str| -> Result<f64, OrbitError> {
+            let value = field(keyword)?;
+            value.parse().map_err(|_| invalid(keyword, value))
+        };
+        let (angle_of_periapsis, ascending_node, inclination) = (number("ARG_OF_PERICENTER")?, number("RA_OF_ASC_NODE")?, number("INCLINATION")?);
+        let eccentricity_within = |low : f64, high : f64| -> Result<f64, OrbitError> {
+            let eccentricity = number("ECCENTRICITY")?;
+            if eccentricity > low && eccentricity < high {
+                Ok(eccentricity)
+            } else {
+                Err(invalid("ECCENTRICITY", &fields["ECCENTRICITY"]))
+            }
+        };
+        match field("ORBIT_TYPE")?.as_str() {
+            "CIRCULAR" => Ok(Orbit::Circular { angle_of_periapsis, ascending_node, inclination, semimajor_axis : number("SEMI_MAJOR_AXIS")? }),
+            "ELLIPTICAL" => Ok(Orbit::Elliptical { angle_of_periapsis, ascending_node, inclination, periapsis : number("PERIAPSIS")?, eccentricity : eccentricity_within(0.0, 1.0)? }),
+            "PARABOLIC" => Ok(Orbit::Parabolic { angle_of_periapsis, ascending_node, inclination, periapsis : number("PERIAPSIS")? }),
+            "HYPERBOLIC" => Ok(Orbit::Hyperbolic { angle_of_periapsis, ascending_node, inclination, periapsis : number("PERIAPSIS")?, eccentricity : eccentricity_within(1.0, f64::INFINITY)? }),
+            other => Err(invalid("ORBIT_TYPE", other))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ Orbit, OrbitError };
+
+    #[test]
+    fn kvn_round_trips_exactly() {
+        let orbits = [
+            Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 1.0 / 3.0, inclination : 0.1, semimajor_axis : 7.0e6 / 3.0 },
+            Orbit::Elliptical { angle_of_periapsis : 2.0f64.sqrt(), ascending_node : 5.9, inclination : 1.7, periapsis : 6.789_123_456_789e6, eccentricity : 1e-9 },
+            Orbit::Parabolic { angle_of_periapsis : 0.3, ascending_node : 0.0, inclination : 0.0, periapsis : 7.1e6 },
+            Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.2, inclination : std::f64::consts::PI, periapsis : 7.2e6, eccentricity : 1.0 + 1e-12 }
+        ];
+        for orbit in orbits.iter() {
+            let read = Orbit::from_kvn(&orbit.to_kvn()).ok().unwrap();
+            assert_eq!(read.to_kvn(), orbit.to_kvn());
+            assert_eq!((read.periapsis(), read.eccentricity(), read.inclination()), (orbit.periapsis(), orbit.eccentricity(), orbit.inclination()));
+            assert_eq!((read.ascending_node(), read.angle_of_periapsis()), (orbit.ascending_node(), orbit.angle_of_periapsis()));
+        }
+    }
+
+    #[test]
+    fn kvn_tolerates_comments_and_whitespace() {
+        let text = "# hand edited\n\n   ORBIT_TYPE=ELLIPTICAL  \nCOMMENT apogee raised\n\tPERIAPSIS   =  7000000 [m]\nECCENTRICITY = 0.25\nINCLINATION = 0.5\nRA_OF_ASC_NODE = 1\nARG_OF_PERICENTER = 2 [rad]\n";
+        let orbit = Orbit::from_kvn(text).ok().unwrap();
+        assert_eq!((orbit.periapsis(), orbit.eccentricity(), orbit.angle_of_periapsis()), (7.0e6, 0.25, 2.0));
+        let hyperbolic_eccentricity = text.replace("0.25", "1.5");
+        assert_eq!(Orbit::from_kvn(&hyperbolic_eccentricity).err(), Some(OrbitError::InvalidValue { keyword : "ECCENTRICITY".to_string(), value : "1.5".to_string() }));
+        assert_eq!(Orbit::from_kvn("ORBIT_TYPE = CIRCULAR").err(), Some(OrbitError::MissingField("ARG_OF_PERICENTER".to_string())));
+    }
+}
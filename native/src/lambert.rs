@@ -0,0 +1,189 @@
+//! Lambert's problem: the two-body arc joining two positions in a given time
+use std::f64::consts::{ PI };
+use crate::{ Anomaly, Body, Orbit, OrbitError, SolverConfig, Vector3 };
+
+/// (departure, arrival) velocities in meters/second of the zero-revolution two-body arc from
+/// `departure` to `arrival` (meters, from the body's center) taking `time_of_flight` seconds. The arc
+/// runs prograde about `normal`: the short way round when `departure × arrival` points along it, the
+/// long way otherwise. Solved with universal variables, Newton-Raphson on the universal anomaly
+/// kept inside a bracket by bisection, until the time of flight is within `config.tolerance` of the
+/// one asked for, relative to it.
+///
+/// `OrbitError::Unreachable` for a non-positive time of flight or positions in line with the
+/// center, 0 or π apart, where the plane of the transfer is undefined; `OrbitError::NotConverged`
+/// past `config.max_iterations`.
+pub fn solve(departure : Vector3, arrival : Vector3, time_of_flight : f64, body : &Body, normal : Vector3, config : &SolverConfig) -> Result<(Vector3, Vector3), OrbitError> {
+    if time_of_flight.is_nan() || time_of_flight <= 0.0 {
+        return Err(OrbitError::Unreachable(format!("time of flight {} s", time_of_flight)));
+    }
+    let (r1, r2) = (departure.magnitude(), arrival.magnitude());
+    let cos_transfer = (departure.dot(arrival) / (r1 * r2)).clamp(-1.0, 1.0);
+    let short_way = departure.cross(arrival).dot(normal) >= 0.0;
+    let transfer_angle = if short_way { cos_transfer.acos() } else { 2.0 * PI - cos_transfer.acos() };
+    let a = transfer_angle.sin() * (r1 * r2 / (1.0 - cos_transfer)).sqrt();
+    if a.is_nan() || a.abs() <= 1e-9 * (r1 + r2) {
+        return Err(OrbitError::Unreachable("departure and arrival are in line with the center".to_string()));
+    }
+
+    let sqrt_k = body.k().sqrt();
+    let y = |z : f64| r1 + r2 + a * (z * stumpff_s(z) - 1.0) / stumpff_c(z).sqrt();
+    // Time of flight for universal anomaly `z`, None where the arc doesn't exist (y < 0)
+    let time = |z : f64| -> Option<f64> {
+        let y = y(z);
+        (y >= 0.0).then(|| ((y / stumpff_c(z)).powf(1.5) * stumpff_s(z) + a * y.sqrt()) / sqrt_k)
+    };
+
+    // Time of flight grows with z, without bound toward the first revolution at 4π²
+    let mut high = 4.0 * PI * PI;
+    let mut low = -4.0 * PI;
+    while time(low).is_some_and(|t| t > time_of_flight) {
+        low *= 2.0;
+    }
+    let mut z = 0.0;
+    for _ in 0..config.max_iterations {
+        let t = match time(z) {
+            Some(t) => t,
+            None => {
+                low = z;
+                z = (low + high) / 2.0;
+                continue;
+            }
+        };
+        if (t - time_of_flight).abs() <= config.tolerance * time_of_flight {
+            let y = y(z);
+            let f = 1.0 - y / r1;
+            let g = a * (y / body.k()).sqrt();
+            let g_dot = 1.0 - y / r2;
+            return Ok(((arrival - departure * f) * (1.0 / g), (arrival * g_dot - departure) * (1.0 / g)));
+        }
+        if t < time_of_flight { low = z } else { high = z }
+        let newton = z - (t - time_of_flight) / time_derivative(z, y(z), a, sqrt_k);
+        z = if newton > low && newton < high { newton } else { (low + high) / 2.0 };
+    }
+    Err(OrbitError::NotConverged { iterations : config.max_iterations })
+}
+
+/// dt/dz of the universal variable time of flight, Curtis eq. 5.43, with its series limit at z = 0
+fn time_derivative(z : f64, y : f64, a : f64, sqrt_k : f64) -> f64 {
+    let (c, s) = (stumpff_c(z), stumpff_s(z));
+    let derivative = if z.abs() < 1e-3 {
+        2f64.sqrt() / 40.0 * y.powf(1.5) + a / 8.0 * (y.sqrt() + a * (1.0 / (2.0 * y)).sqrt())
+    } else {
+        (y / c).powf(1.5) * ((c - 1.5 * s / c) / (2.0 * z) + 0.75 * s * s / c) + a / 8.0 * (3.0 * s / c * y.sqrt() + a * (c / y).sqrt())
+    };
+    derivative / sqrt_k
+}
+
+/// Stumpff `C(z) = (1 - cos √z)/z`, continued through z = 0 and to negative z
+fn stumpff_c(z : f64) -> f64 {
+    if z > 1e-6 {
+        (1.0 - z.sqrt().cos()) / z
+    } else if z < -1e-6 {
+        ((-z).sqrt().cosh() - 1.0) / -z
+    } else {
+        1.0 / 2.0 - z / 24.0
+    }
+}
+
+/// Stumpff `S(z) = (√z - sin √z)/√z³`, continued through z = 0 and to negative z
+fn stumpff_s(z : f64) -> f64 {
+    if z > 1e-6 {
+        let root = z.sqrt();
+        (root - root.sin()) / root.powi(3)
+    } else if z < -1e-6 {
+        let root = (-z).sqrt();
+        (root.sinh() - root) / root.powi(3)
+    } else {
+        1.0 / 6.0 - z / 120.0
+    }
+}
+
+impl Orbit {
+
+    /// (departure burn, arrival burn, total delta-v) in meters/second for the two-impulse transfer
+    /// leaving this orbit at `departure` and meeting `target` at `arrival` `time_of_flight` seconds
+    /// later, along the Lambert arc between the two positions running prograde with this orbit. The
+    /// burns are vectors in inertial coordinates; the total is the sum of their magnitudes. The arc
+    /// is solved to `config`; errors are those of `solve`.
+    pub fn optimal_transfer(&self, target : &Orbit, body : &Body, departure : &Anomaly, arrival : &Anomaly, time_of_flight : f64, config : &SolverConfig) -> Result<(Vector3, Vector3, f64), OrbitError> {
+        let start = self.state_vector(body, departure);
+        let end = target.state_vector(body, arrival);
+        let (leaving, arriving) = solve(start.position(), end.position(), time_of_flight, body, self.normal(), config)?;
+        let (first, second) = (leaving - start.velocity(), end.velocity() - arriving);
+        Ok((first, second, first.magnitude() + second.magnitude()))
+    }
+
+    /// Total delta-v (meters/second) of `optimal_transfer` for every pair of departure and arrival
+    /// time, row `i` column `j` leaving at `departure_times[i]` and arriving at `arrival_times[j]`.
+    /// Times are seconds after `start.time_ms`, with this orbit's object at `start` and the target's
+    /// at `target_start` on the same clock. Each arc is solved to `config`. Pairs arriving no later
+    /// than they leave, or whose Lambert solve fails, are NaN.
+    #[allow(clippy::too_many_arguments)]
+    pub fn porkchop(&self, target : &Orbit, body : &Body, start : &Anomaly, target_start : &Anomaly, departure_times : &[f64], arrival_times : &[f64], config : &SolverConfig) -> Vec<Vec<f64>> {
+        let offset = (start.time_ms as f64 - target_start.time_ms as f64) / 1000.0;
+        let arrivals : Vec<Anomaly> = arrival_times.iter().map(|&t| target.next_anomaly(body, target_start, t + offset)).collect();
+        departure_times.iter()
+            .map(|&leave| {
+                let departure = self.next_anomaly(body, start, leave);
+                arrival_times.iter().zip(arrivals.iter())
+                    .map(|(&arrive, arrival)| {
+                        self.optimal_transfer(target, body, &departure, arrival, arrive - leave, config).map_or(f64::NAN, |(_, _, total)| total)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ earth, StateVector };
+
+    #[test]
+    fn matches_curtis_example() {
+        // Curtis, Orbital Mechanics for Engineering Students, Example 5.2
+        let body = Body::builder().gravitational_parameter(398_600e9).radius(6.378e6).build().unwrap();
+        let departure = Vector3::new(5_000e3, 10_000e3, 2_100e3);
+        let arrival = Vector3::new(-14_600e3, 2_500e3, 7_000e3);
+        let (v1, v2) = solve(departure, arrival, 3600.0, &body, Vector3::k_hat(), &SolverConfig::default()).unwrap();
+        assert!((v1 - Vector3::new(-5.9925e3, 1.9254e3, 3.2456e3)).magnitude() < 0.1);
+        assert!((v2 - Vector3::new(-3.3125e3, -4.1966e3, -0.385_29e3)).magnitude() < 0.1);
+        assert!(solve(departure, arrival, -1.0, &body, Vector3::k_hat(), &SolverConfig::default()).is_err());
+    }
+
+    #[test]
+    fn transfer_arc_reaches_the_target() {
+        let body = earth();
+        let inner = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.1, semimajor_axis : 7.0e6 };
+        let outer = Orbit::Elliptical { angle_of_periapsis : 0.5, ascending_node : 0.3, inclination : 0.2, periapsis : 1.2e7, eccentricity : 0.1 };
+        let (departure, arrival) = (Anomaly::from_true(&inner, 0.0, 0), Anomaly::from_true(&outer, 2.0, 0));
+        let (first, second, total) = inner.optimal_transfer(&outer, &body, &departure, &arrival, 5000.0, &SolverConfig::default()).unwrap();
+        assert!((total - first.magnitude() - second.magnitude()).abs() < 1e-9);
+
+        let start = inner.state_vector(&body, &departure);
+        let burned = StateVector::new(start.position(), start.velocity() + first, 0);
+        let arc = Orbit::from_state_vector(&burned, &body).unwrap();
+        let (_, at_start) = Orbit::from_burnout(&burned, &body).unwrap();
+        let end = arc.state_vector(&body, &arc.next_anomaly(&body, &at_start, 5000.0));
+        let target = outer.state_vector(&body, &arrival);
+        assert!((end.position() - target.position()).magnitude() < 1e-2);
+        assert!((end.velocity() + second - target.velocity()).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn porkchop_marks_infeasible_cells() {
+        let body = earth();
+        let inner = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.1, semimajor_axis : 7.0e6 };
+        let outer = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.2, inclination : 0.15, semimajor_axis : 1.1e7 };
+        let (start, target_start) = (Anomaly::from_true(&inner, 0.0, 10_000), Anomaly::from_true(&outer, 1.0, 0));
+        let grid = inner.porkchop(&outer, &body, &start, &target_start, &[0.0, 600.0], &[300.0, 3000.0, 4000.0], &SolverConfig::default());
+        assert_eq!((grid.len(), grid[0].len()), (2, 3));
+        assert!(grid[1][0].is_nan());
+        let departure = inner.next_anomaly(&body, &start, 600.0);
+        let arrival = outer.next_anomaly(&body, &target_start, 4010.0);
+        let (_, _, total) = inner.optimal_transfer(&outer, &body, &departure, &arrival, 3400.0, &SolverConfig::default()).unwrap();
+        assert!((grid[1][2] - total).abs() < 1e-9);
+        assert!(grid[0].iter().all(|cell| cell.is_finite()));
+    }
+}
@@ -0,0 +1,58 @@
+//! Launch opportunities from a site on a rotating body
+use std::f64::consts::{ PI };
+use crate::{ Body, Orbit };
+
+impl Orbit {
+
+    /// Seconds, in increasing order within `[0, duration]`, at which a launch site at geocentric
+    /// `latitude` and body-fixed `longitude` (radians) rotates through this orbit's plane, the
+    /// in-plane opportunities for a launch to rendezvous with an object on it. Time zero is when the
+    /// body's prime meridian is on the inertial x axis, as for `ground_track`. There are two crossings
+    /// per sidereal day, one for a launch heading north and one heading south, merging into one as
+    /// the latitude reaches the inclination; sites at higher latitudes than the plane reaches, and
+    /// every site on a body that doesn't rotate, get none.
+    pub fn launch_windows(&self, body : &Body, latitude : f64, longitude : f64, duration : f64) -> Vec<f64> {
+        let rate = body.rotation_rate();
+        let normal = self.normal();
+        // The site direction (cos φ cos θ, cos φ sin θ, sin φ) lies in the plane when
+        // cos φ·|n_xy|·cos(θ - α) = -n_z sin φ, θ being the site's inertial longitude
+        let in_plane = latitude.cos() * normal.x.hypot(normal.y);
+        let ratio = -normal.z * latitude.sin() / in_plane;
+        if rate == 0.0 || ratio.is_nan() || ratio.abs() > 1.0 {
+            return Vec::new();
+        }
+        let (direction, offset) = (normal.y.atan2(normal.x), ratio.acos());
+        let day = 2.0 * PI / rate.abs();
+        let mut windows : Vec<f64> = [direction + offset, direction - offset].iter()
+            .flat_map(|&crossing| {
+                let first = ((crossing - longitude) / rate).rem_euclid(day);
+                (0..).map(move |j| first + j as f64 * day).take_while(move |&t| t <= duration)
+            })
+            .collect();
+        windows.sort_by(|a, b| a.total_cmp(b));
+        windows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ Body, Orbit, Vector3 };
+
+    #[test]
+    fn site_crosses_the_plane_twice_a_day() {
+        let body = Body::builder().mass(5.972e24).radius(6.371e6).rotation_period(86_164.1).build().unwrap();
+        let station = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 1.2, inclination : 51.6f64.to_radians(), semimajor_axis : 6.78e6 };
+        let (latitude, longitude) = (28.5f64.to_radians(), -80.6f64.to_radians());
+        let windows = station.launch_windows(&body, latitude, longitude, 3.0 * 86_164.1);
+        assert_eq!(windows.len(), 6);
+        for &t in windows.iter() {
+            let angle = longitude + body.rotation_rate() * t;
+            let site = Vector3::new(latitude.cos() * angle.cos(), latitude.cos() * angle.sin(), latitude.sin());
+            assert!(site.dot(station.normal()).abs() < 1e-9);
+        }
+        // Too far north to reach the plane, or on a body that doesn't turn
+        assert!(station.launch_windows(&body, 60f64.to_radians(), 0.0, 86_164.1).is_empty());
+        let still = Body { rotation_period : None, ..body };
+        assert!(station.launch_windows(&still, latitude, longitude, 86_164.1).is_empty());
+    }
+}
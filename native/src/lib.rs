@@ -1,44 +1,227 @@
 extern crate affine_transforms;
 
-use affine_transforms::matrices::{ AffineMatrix };
+pub mod vector3;
+pub mod state_vector;
+pub mod geodesy;
+pub mod elements;
+pub mod forces;
+pub mod ephemeris;
+pub mod ccsds;
+pub mod error;
+pub mod equinoctial;
+pub mod solvers;
+pub mod rocket;
+pub mod lambert;
+pub mod propagation;
+pub mod sgp4;
+pub mod chebyshev;
+pub mod cr3bp;
+mod kepler;
+mod perturbations;
+mod geometry;
+mod maneuvers;
+mod secular;
+mod frames;
+mod series;
+mod lighting;
+mod averaging;
+mod decay;
+mod conjunction;
+mod ground;
+mod degrees;
+mod kvn;
+mod determination;
+mod launch;
+#[cfg(feature = "uom")]
+mod units;
+#[cfg(feature = "test_vectors")]
+pub mod test_vectors;
+
+pub use vector3::Vector3;
+pub use state_vector::{ StateVector, Frame };
+pub use elements::KeplerianElements;
+pub use series::SERIES_MAX_ECCENTRICITY;
+pub use secular::laplace_coefficient;
+pub use kepler::{ true_to_eccentric, eccentric_to_mean, mean_to_eccentric, eccentric_to_true };
+pub use kepler::{ hyperbolic_true_to_anomaly, hyperbolic_anomaly_to_mean, mean_to_hyperbolic_anomaly, hyperbolic_anomaly_to_true };
+pub use kepler::{ parabolic_true_to_barker, barker_to_mean, mean_to_barker, barker_to_true };
+pub use ephemeris::Ephemeris;
+pub use error::OrbitError;
+pub use equinoctial::{ EquinoctialElements, ModifiedEquinoctialElements };
+pub use solvers::{ KeplerSolver, NewtonSolver, HalleySolver, DanbySolver, SolverConfig, eccentric_from_mean_bisect };
+pub use propagation::{ Propagator, KeplerPropagator, Rk4Propagator, integrate_adaptive, integrate_symplectic };
+
 use std::f64::consts::{ PI };
 
-struct Body {
+/// Newtonian constant of gravitation, m^3/(kg s^2)
+pub const G : f64 = 6.674_30e-11;
+
+/// Age in days past which propagated elements (e.g. from a TLE) should no longer be trusted
+pub const DEFAULT_MAX_EPOCH_AGE_DAYS : f64 = 14.0;
+
+/// Eccentricities within this of 0 or 1 are classified as circular or parabolic
+pub const ECCENTRICITY_TOLERANCE : f64 = 1e-9;
+
+/// Radians, the obliquity of the ecliptic at J2000 (23.4393°), for `Orbit::to_ecliptic` and `Orbit::to_equatorial`
+pub const OBLIQUITY_J2000 : f64 = 23.4393 * PI / 180.0;
+
+/// Radians/second in one revolution/day, for mean motions in the TLE convention
+pub const REVOLUTIONS_PER_DAY : f64 = 2.0 * PI / 86_400.0;
+
+const MS_PER_DAY : f64 = 86_400_000.0;
+
+/// Julian date of the Unix epoch, which `Anomaly::time_ms` is measured from
+const UNIX_EPOCH_JD : f64 = 2_440_587.5;
+
+pub struct Body {
     /// kilograms
-    mass : f64, 
-    /// meters
-    radius : f64
+    pub mass : f64,
+    /// meters, equatorial
+    pub radius : f64,
+    /// seconds, sidereal. None for a body treated as not rotating
+    pub rotation_period : Option<f64>,
+    /// (equatorial - polar) / equatorial radius, zero for a sphere
+    pub flattening : f64,
+    /// Zonal harmonic coefficients of the gravity field, unnormalized and referred to `radius`. None
+    /// leaves the term out; see `forces::zonal_acceleration`.
+    pub j2 : Option<f64>,
+    pub j3 : Option<f64>,
+    pub j4 : Option<f64>
 }
 
 impl Body {
     /// Units m^3/s^2 (Mass * G)
-    fn k(&self, G : f64) -> f64 { 
+    pub fn k(&self) -> f64 {
         self.mass * G
     }
+
+    /// Meters from the center to the surface at a geocentric latitude (radians)
+    pub fn geocentric_radius(&self, latitude : f64) -> f64 {
+        let polar = self.radius * (1.0 - self.flattening);
+        let (sin_lat, cos_lat) = latitude.sin_cos();
+        self.radius * polar / ((polar * cos_lat).powi(2) + (self.radius * sin_lat).powi(2)).sqrt()
+    }
+
+    /// Meters from the surface directly beneath a position to the position, along the radius
+    fn height_of(&self, position : Vector3) -> f64 {
+        let r = position.magnitude();
+        r - self.geocentric_radius((position.z / r).asin())
+    }
+
+    /// Meters/second needed to escape from `radius` meters, `sqrt(2k/r)`
+    pub fn escape_velocity(&self, radius : f64) -> f64 {
+        (2.0 * self.k() / radius).sqrt()
+    }
+
+    /// Radians/second about the z axis
+    pub fn rotation_rate(&self) -> f64 {
+        self.rotation_period.map_or(0.0, |period| 2.0 * PI / period)
+    }
+
+    /// A `BodyBuilder` with nothing set
+    pub fn builder() -> BodyBuilder {
+        BodyBuilder::default()
+    }
+}
+
+/// Fluent construction of a `Body`, naming each field as it is set. Mass (or the gravitational
+/// parameter) and radius are required; the rest default to a sphere that doesn't rotate and has
+/// no zonal harmonics.
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct BodyBuilder {
+    mass : Option<f64>,
+    radius : Option<f64>,
+    rotation_period : Option<f64>,
+    flattening : f64,
+    j2 : Option<f64>,
+    j3 : Option<f64>,
+    j4 : Option<f64>
+}
+
+impl BodyBuilder {
+    /// kilograms
+    pub fn mass(mut self, mass : f64) -> BodyBuilder {
+        self.mass = Some(mass);
+        self
+    }
+
+    /// m^3/s^2, in place of the mass, since published values of GM are far more precise than G
+    pub fn gravitational_parameter(mut self, k : f64) -> BodyBuilder {
+        self.mass = Some(k / G);
+        self
+    }
+
+    /// meters, equatorial
+    pub fn radius(mut self, radius : f64) -> BodyBuilder {
+        self.radius = Some(radius);
+        self
+    }
+
+    /// seconds, sidereal
+    pub fn rotation_period(mut self, rotation_period : f64) -> BodyBuilder {
+        self.rotation_period = Some(rotation_period);
+        self
+    }
+
+    pub fn flattening(mut self, flattening : f64) -> BodyBuilder {
+        self.flattening = flattening;
+        self
+    }
+
+    pub fn j2(mut self, j2 : f64) -> BodyBuilder {
+        self.j2 = Some(j2);
+        self
+    }
+
+    pub fn j3(mut self, j3 : f64) -> BodyBuilder {
+        self.j3 = Some(j3);
+        self
+    }
+
+    pub fn j4(mut self, j4 : f64) -> BodyBuilder {
+        self.j4 = Some(j4);
+        self
+    }
+
+    /// `OrbitError::MissingField` naming the mass or radius if either was never set
+    pub fn build(self) -> Result<Body, OrbitError> {
+        Ok(Body {
+            mass : self.mass.ok_or_else(|| OrbitError::MissingField("mass".to_string()))?,
+            radius : self.radius.ok_or_else(|| OrbitError::MissingField("radius".to_string()))?,
+            rotation_period : self.rotation_period,
+            flattening : self.flattening,
+            j2 : self.j2,
+            j3 : self.j3,
+            j4 : self.j4
+        })
+    }
 }
 
-enum Orbit {
+pub enum Orbit {
     Circular {
-        angle_of_periapsis : f64,    
+        angle_of_periapsis : f64,
         ascending_node : f64,
         inclination : f64,
-        semimajor_axis : f64,        
+        semimajor_axis : f64,
     },
     Elliptical {
-        angle_of_periapsis : f64,    
+        angle_of_periapsis : f64,
         ascending_node : f64,
         inclination : f64,
-        periapsis : f64,  
+        periapsis : f64,
         eccentricity : f64,
     },
-    Parabolic { 
-        angle_of_periapsis : f64,    
+    Parabolic {
+        angle_of_periapsis : f64,
         ascending_node : f64,
         inclination : f64,
-        periapsis : f64,   
+        periapsis : f64,
     },
-    Hyperbolic { 
-        angle_of_periapsis : f64,    
+    Hyperbolic {
+        angle_of_periapsis : f64,
         ascending_node : f64,
         inclination : f64,
         periapsis : f64,
@@ -48,45 +231,1405 @@ enum Orbit {
 
 
 impl Orbit {
-    
-    fn eccentricity(&self) -> f64 {
+
+    /// Picks the variant matching the eccentricity, see `ECCENTRICITY_TOLERANCE`.
+    /// Periapsis in meters, angles in radians.
+    pub fn from_elements(periapsis : f64, eccentricity : f64, inclination : f64, ascending_node : f64, angle_of_periapsis : f64) -> Orbit {
+        if eccentricity < ECCENTRICITY_TOLERANCE {
+            Orbit::Circular { angle_of_periapsis, ascending_node, inclination, semimajor_axis : periapsis }
+        } else if (eccentricity - 1.0).abs() < ECCENTRICITY_TOLERANCE {
+            Orbit::Parabolic { angle_of_periapsis, ascending_node, inclination, periapsis }
+        } else if eccentricity < 1.0 {
+            Orbit::Elliptical { angle_of_periapsis, ascending_node, inclination, periapsis, eccentricity }
+        } else {
+            Orbit::Hyperbolic { angle_of_periapsis, ascending_node, inclination, periapsis, eccentricity }
+        }
+    }
+
+    /// Orbit through an inertial state. Circular orbits take an angle of periapsis of zero,
+    /// measuring true anomaly from the node. Angles are measured in the direction of motion about
+    /// the angular momentum, so retrograde inclinations in `(π/2, π]` need no special casing.
+    /// Equatorial orbits have no node; by convention it is put on the x axis (ascending node of
+    /// zero), so a retrograde equatorial orbit's angle of periapsis runs clockwise seen from +z.
+    /// Perifocal states are brought over to `Frame::Eci` first; body-fixed ones fail with
+    /// `OrbitError::InvalidValue`, as in `StateVector::inertial`.
+    pub fn from_state_vector(state : &StateVector, body : &Body) -> Result<Orbit, OrbitError> {
+        Ok(Orbit::from_burnout(state, body)?.0)
+    }
+
+    /// Orbit from specific orbital energy (J/kg) and specific angular momentum (m^2/s), the conserved
+    /// quantities of two-body motion, with the variant chosen from the resulting eccentricity.
+    /// Angles in radians. With `flight_direction` true the orbit runs counterclockwise about the
+    /// normal set by `inclination` and `ascending_node`, prograde for inclinations under π/2; false
+    /// flies the same plane the other way round, as inclination π - i with the node at Ω + π and the
+    /// periapsis kept at the point in space the given angles put it.
+    pub fn from_energy_and_momentum(energy : f64, angular_momentum : f64, body : &Body, inclination : f64, ascending_node : f64, angle_of_periapsis : f64, flight_direction : bool) -> Orbit {
+        let (inclination, ascending_node, angle_of_periapsis) = if flight_direction {
+            (inclination, ascending_node, angle_of_periapsis)
+        } else {
+            (PI - inclination, (ascending_node + PI).rem_euclid(2.0 * PI), (PI - angle_of_periapsis).rem_euclid(2.0 * PI))
+        };
+        let k = body.k();
+        let parameter = angular_momentum * angular_momentum / k;
+        // e² = 1 + 2εh²/k², equivalent to h² = k·a·(1 - e²) with a = -k/2ε but defined for ε = 0 too
+        let eccentricity = (1.0 + 2.0 * energy * parameter / k).max(0.0).sqrt();
+        Orbit::from_elements(parameter / (1.0 + eccentricity), eccentricity, inclination, ascending_node, angle_of_periapsis)
+    }
+
+    /// Orbit from its mean motion in radians/second, inverting `mean_motion`. TLEs and most catalogs
+    /// quote revolutions/day; multiply those by `REVOLUTIONS_PER_DAY` first. Angles in radians.
+    pub fn from_mean_motion(body : &Body, mean_motion : f64, eccentricity : f64, inclination : f64, ascending_node : f64, angle_of_periapsis : f64) -> Orbit {
+        let k = body.k();
+        let periapsis = if (eccentricity - 1.0).abs() < ECCENTRICITY_TOLERANCE {
+            (k / (2.0 * mean_motion * mean_motion)).cbrt()
+        } else {
+            (k / (mean_motion * mean_motion)).cbrt() * (1.0 - eccentricity).abs()
+        };
+        Orbit::from_elements(periapsis, eccentricity, inclination, ascending_node, angle_of_periapsis)
+    }
+
+    /// Closed orbit with the given Keplerian period in seconds, inverting `orbital_period`. Angles in
+    /// radians. `OrbitError::InvalidValue` for a period that isn't positive and finite or an
+    /// eccentricity outside `[0, 1)`, which has no period.
+    pub fn from_period(body : &Body, period : f64, eccentricity : f64, inclination : f64, ascending_node : f64, angle_of_periapsis : f64) -> Result<Orbit, OrbitError> {
+        if !period.is_finite() || period <= 0.0 {
+            return Err(OrbitError::InvalidValue { keyword : "period".to_string(), value : period.to_string() });
+        }
+        if eccentricity.is_nan() || !(0.0..1.0).contains(&eccentricity) {
+            return Err(OrbitError::InvalidValue { keyword : "eccentricity".to_string(), value : eccentricity.to_string() });
+        }
+        Ok(Orbit::from_mean_motion(body, 2.0 * PI / period, eccentricity, inclination, ascending_node, angle_of_periapsis))
+    }
+
+    /// Orbit through a point `radius` meters from the center, moving at `speed` meters/second and
+    /// `flight_path_angle` radians above the local horizontal; the scalar counterpart of
+    /// `from_state_vector`. The plane is given by `inclination` and `ascending_node`, and the point's
+    /// place in it by its `argument_of_latitude`, the angle from the ascending node (ω + ν). The
+    /// variant follows from the energy and angular momentum, as in `from_energy_and_momentum`.
+    pub fn from_radius_velocity_fpa(radius : f64, speed : f64, flight_path_angle : f64, body : &Body, inclination : f64, ascending_node : f64, argument_of_latitude : f64) -> Orbit {
+        let k = body.k();
+        let energy = speed * speed / 2.0 - k / radius;
+        let angular_momentum = radius * speed * flight_path_angle.cos();
+        // tan ν = q sin γ cos γ / (q cos² γ - 1), with q = r v² / k
+        let q = radius * speed * speed / k;
+        let (sin, cos) = flight_path_angle.sin_cos();
+        let true_anomaly = (q * sin * cos).atan2(q * cos * cos - 1.0);
+        let angle_of_periapsis = (argument_of_latitude - true_anomaly).rem_euclid(2.0 * PI);
+        Orbit::from_energy_and_momentum(energy, angular_momentum, body, inclination, ascending_node, angle_of_periapsis, true)
+    }
+
+    /// `from_state_vector` for a sub-orbital burnout, along with the anomaly at burnout
+    pub fn from_burnout(burnout : &StateVector, body : &Body) -> Result<(Orbit, Anomaly), OrbitError> {
+        let burnout = burnout.inertial()?;
+        Ok(Orbit::from_inertial(burnout.position(), burnout.velocity(), burnout.time_ms(), body))
+    }
+
+    /// `from_burnout` for a position and velocity already in `Frame::Eci`
+    pub(crate) fn from_inertial(position : Vector3, velocity : Vector3, time_ms : u64, body : &Body) -> (Orbit, Anomaly) {
+        let (orbit, true_anomaly) = elements_from_state(position, velocity, body.k());
+        let anomaly = Anomaly::from_true(&orbit, true_anomaly, time_ms);
+        (orbit, anomaly)
+    }
+
+    pub fn eccentricity(&self) -> f64 {
+        match *self {
+            Orbit::Circular { .. }=> 0.0,
+            Orbit::Elliptical { eccentricity, .. } => eccentricity,
+            Orbit::Parabolic { .. } => 1.0,
+            Orbit::Hyperbolic { eccentricity, .. } => eccentricity
+        }
+    }
+
+    /// Radians, see `angle_of_periapsis_deg`
+    pub fn angle_of_periapsis(&self) -> f64 {
+        match *self {
+            Orbit::Circular { angle_of_periapsis, .. }
+            | Orbit::Elliptical { angle_of_periapsis, .. }
+            | Orbit::Parabolic { angle_of_periapsis, .. }
+            | Orbit::Hyperbolic { angle_of_periapsis, .. } => angle_of_periapsis
+        }
+    }
+
+    /// Radians, see `ascending_node_deg`
+    pub fn ascending_node(&self) -> f64 {
+        match *self {
+            Orbit::Circular { ascending_node, .. }
+            | Orbit::Elliptical { ascending_node, .. }
+            | Orbit::Parabolic { ascending_node, .. }
+            | Orbit::Hyperbolic { ascending_node, .. } => ascending_node
+        }
+    }
+
+    /// Radians, see `inclination_deg`
+    pub fn inclination(&self) -> f64 {
         match *self {
-            Orbit::Circular { .. }=> 0.0, 
-            Orbit::Elliptical { eccentricity, .. } => eccentricity, 
-            Orbit::Parabolic { .. } => 1.0,    
-            Orbit::Hyperbolic { eccentricity, .. } => eccentricity  
+            Orbit::Circular { inclination, .. }
+            | Orbit::Elliptical { inclination, .. }
+            | Orbit::Parabolic { inclination, .. }
+            | Orbit::Hyperbolic { inclination, .. } => inclination
         }
     }
 
-    // fn get_angle_of_descending_node(&self) -> f64 { *self.ascending_node + PI }            
+    /// Meters from the focus at closest approach
+    pub fn periapsis(&self) -> f64 {
+        match *self {
+            Orbit::Circular { semimajor_axis, .. } => semimajor_axis,
+            Orbit::Elliptical { periapsis, .. }
+            | Orbit::Parabolic { periapsis, .. }
+            | Orbit::Hyperbolic { periapsis, .. } => periapsis
+        }
+    }
+
+    /// Meters, negative for hyperbolic orbits and undefined for parabolic ones
+    pub fn semimajor_axis(&self) -> Option<f64> {
+        match *self {
+            Orbit::Circular { semimajor_axis, .. } => Some(semimajor_axis),
+            Orbit::Parabolic { .. } => None,
+            Orbit::Elliptical { periapsis, eccentricity, .. }
+            | Orbit::Hyperbolic { periapsis, eccentricity, .. } => Some(periapsis / (1.0 - eccentricity))
+        }
+    }
+
+    /// Meters from the focus at the farthest point, None for open orbits
+    pub fn apoapsis(&self) -> Option<f64> {
+        match *self {
+            Orbit::Circular { semimajor_axis, .. } => Some(semimajor_axis),
+            Orbit::Elliptical { periapsis, eccentricity, .. } => Some(periapsis * (1.0 + eccentricity) / (1.0 - eccentricity)),
+            Orbit::Parabolic { .. } | Orbit::Hyperbolic { .. } => None
+        }
+    }
+
+    /// Radians in `[0, 2π)`, `Ω + ω + ν`. Unlike its parts this stays defined as e and i go to zero:
+    /// for a circular orbit ω and ν are individually arbitrary and only the argument of latitude
+    /// `ω + ν` is meaningful; for an equatorial orbit Ω is arbitrary (taken as 0) and only the
+    /// longitude of periapsis `Ω + ω` is; for one both circular and equatorial only the sum is.
+    pub fn true_longitude(&self, anomaly : &Anomaly) -> f64 {
+        (self.ascending_node() + self.angle_of_periapsis() + anomaly.true_anomaly).rem_euclid(2.0 * PI)
+    }
+
+    /// Longitude of periapsis `ϖ = Ω + ω`, radians in `[0, 2π)`, measured along the reference plane
+    /// to the node and then along the orbit, and defined even when the node isn't
+    pub fn longitude_of_periapsis(&self) -> f64 {
+        (self.ascending_node() + self.angle_of_periapsis()).rem_euclid(2.0 * PI)
+    }
+
+    /// Mean longitude `L = ϖ + M` at the given anomaly, radians in `[0, 2π)`
+    pub fn mean_longitude(&self, anomaly : &Anomaly) -> f64 {
+        (self.longitude_of_periapsis() + anomaly.mean_anomaly).rem_euclid(2.0 * PI)
+    }
+
+    /// Semi-latus rectum, meters
+    pub fn parameter(&self) -> f64 {
+        self.periapsis() * (1.0 + self.eccentricity())
+    }
+
+    /// Meters from the focus at the given anomaly
+    pub fn distance_from_parent(&self, anomaly : &Anomaly) -> f64 {
+        self.parameter() / (1.0 + self.eccentricity() * anomaly.true_anomaly.cos())
+    }
+
+    /// Specific orbital energy in J/kg, `-k/2a`: negative when bound, zero for parabolic orbits
+    pub fn total_energy(&self, body : &Body) -> f64 {
+        self.semimajor_axis().map_or(0.0, |a| -body.k() / (2.0 * a))
+    }
+
+    /// Specific angular momentum in m^2/s, `sqrt(k·p)`
+    pub fn angular_momentum(&self, body : &Body) -> f64 {
+        (body.k() * self.parameter()).sqrt()
+    }
+
+    /// Square meters/second swept by the radius vector, `h/2`, constant by Kepler's second law
+    pub fn areal_velocity(&self, body : &Body) -> f64 {
+        self.angular_momentum(body) / 2.0
+    }
+
+    /// Radians/second at which the true anomaly advances, `h/r²`
+    pub fn true_anomaly_rate(&self, body : &Body, anomaly : &Anomaly) -> f64 {
+        self.angular_momentum(body) / self.distance_from_parent(anomaly).powi(2)
+    }
+
+    /// Speed in meters/second at a distance (meters) from the focus, `sqrt(k(2/r - 1/a))`
+    pub fn vis_viva(&self, body : &Body, radius : f64) -> f64 {
+        let inverse_a = self.semimajor_axis().map_or(0.0, |a| 1.0 / a);
+        (body.k() * (2.0 / radius - inverse_a)).sqrt()
+    }
+
+    /// `vis_viva` at `altitude` meters above the body's equatorial radius, None when no point on the
+    /// orbit is at that altitude: below periapsis or above apoapsis
+    pub fn speed_at_altitude(&self, body : &Body, altitude : f64) -> Option<f64> {
+        let radius = body.radius + altitude;
+        // Allow for rounding in the altitude of a circular orbit or an apsis
+        let slack = radius * 1e-12;
+        let above_apoapsis = self.apoapsis().is_some_and(|apoapsis| radius > apoapsis + slack);
+        if radius < self.periapsis() - slack || above_apoapsis {
+            return None;
+        }
+        Some(self.vis_viva(body, radius))
+    }
+
+    /// ((radius, speed) at periapsis, (radius, speed) at apoapsis), meters and meters/second by
+    /// `vis_viva`. The apoapsis is None for open orbits.
+    pub fn apsis_kinematics(&self, body : &Body) -> ((f64, f64), Option<(f64, f64)>) {
+        let periapsis = self.periapsis();
+        let apoapsis = self.apoapsis().map(|apoapsis| (apoapsis, self.vis_viva(body, apoapsis)));
+        ((periapsis, self.vis_viva(body, periapsis)), apoapsis)
+    }
+
+    /// Radians above the local horizontal at the given anomaly, positive while climbing away from periapsis
+    pub fn flight_path_angle(&self, anomaly : &Anomaly) -> f64 {
+        let e = self.eccentricity();
+        let (s, c) = anomaly.true_anomaly.sin_cos();
+        (e * s).atan2(1.0 + e * c)
+    }
+
+    /// (`distance_from_parent`, speed, `flight_path_angle`) at the given anomaly, sharing one
+    /// evaluation of the parameter, angular momentum, and true anomaly trig between them
+    pub fn kinematics(&self, body : &Body, anomaly : &Anomaly) -> (f64, f64, f64) {
+        let p = self.parameter();
+        let e = self.eccentricity();
+        let (s, c) = anomaly.true_anomaly.sin_cos();
+        let k_over_h = body.k() / (body.k() * p).sqrt();
+        // Radial and transverse velocity are (k/h) e sin ν and (k/h)(1 + e cos ν)
+        let (radial, transverse) = (e * s, 1.0 + e * c);
+        (p / transverse, k_over_h * radial.hypot(transverse), radial.atan2(transverse))
+    }
+
+    /// Meters above the body's surface at the given anomaly, negative beneath it. Measured radially
+    /// to the surface at the position's geocentric latitude, so it follows the body's flattening.
+    pub fn altitude(&self, body : &Body, anomaly : &Anomaly) -> f64 {
+        body.height_of(self.state_vector(body, anomaly).position())
+    }
+
+    /// Inertial unit vector along the velocity at the given anomaly, the ram direction
+    pub fn velocity_direction(&self, body : &Body, anomaly : &Anomaly) -> Vector3 {
+        self.state_vector(body, anomaly).velocity().unit()
+    }
+
+    /// Inertial unit vector from the position at the given anomaly toward the body's center. This is
+    /// geocentric nadir; on a flattened body the local vertical is off it by up to about `flattening`
+    /// radians.
+    pub fn nadir_direction(&self, body : &Body, anomaly : &Anomaly) -> Vector3 {
+        (-self.state_vector(body, anomaly).position()).unit()
+    }
+
+    /// Meters above the body's surface at periapsis, negative beneath it
+    pub fn periapsis_altitude(&self, body : &Body) -> f64 {
+        body.height_of(self.position_at(body, 0.0))
+    }
+
+    /// Meters above the body's surface at apoapsis, None for open orbits
+    pub fn apoapsis_altitude(&self, body : &Body) -> Option<f64> {
+        self.apoapsis().map(|_| body.height_of(self.position_at(body, PI)))
+    }
+
+    /// Whether the periapsis dips beneath the body's (equatorial) radius, so the orbit would impact.
+    /// Surface crossings and impacts treat the body as a sphere of its equatorial radius.
+    pub fn intersects_surface(&self, body : &Body) -> bool {
+        self.periapsis() < body.radius
+    }
+
+    /// Same shape, reoriented to the given angles (radians)
+    pub(crate) fn with_orientation(&self, inclination : f64, ascending_node : f64, angle_of_periapsis : f64) -> Orbit {
+        match *self {
+            Orbit::Circular { semimajor_axis, .. } =>
+                Orbit::Circular { angle_of_periapsis, ascending_node, inclination, semimajor_axis },
+            Orbit::Elliptical { periapsis, eccentricity, .. } =>
+                Orbit::Elliptical { angle_of_periapsis, ascending_node, inclination, periapsis, eccentricity },
+            Orbit::Parabolic { periapsis, .. } =>
+                Orbit::Parabolic { angle_of_periapsis, ascending_node, inclination, periapsis },
+            Orbit::Hyperbolic { periapsis, eccentricity, .. } =>
+                Orbit::Hyperbolic { angle_of_periapsis, ascending_node, inclination, periapsis, eccentricity }
+        }
+    }
+
+    /// Same shape, oriented so its normal and periapsis point along the given unit vectors
+    pub(crate) fn with_axes(&self, normal : Vector3, periapsis : Vector3) -> Orbit {
+        let inclination = normal.z.clamp(-1.0, 1.0).acos();
+        let node_vector = Vector3::k_hat().cross(normal);
+        let (ascending_node, node) = if node_vector.magnitude() > ECCENTRICITY_TOLERANCE {
+            (node_vector.y.atan2(node_vector.x).rem_euclid(2.0 * PI), node_vector.unit())
+        } else {
+            (0.0, Vector3::i_hat())
+        };
+        let angle_of_periapsis = node.cross(periapsis).dot(normal).atan2(node.dot(periapsis)).rem_euclid(2.0 * PI);
+        self.with_orientation(inclination, ascending_node, angle_of_periapsis)
+    }
+
+    /// Rotates a vector from the perifocal frame (x toward periapsis, z along the orbit normal) to inertial
+    pub fn perifocal_to_inertial(&self, v : Vector3) -> Vector3 {
+        v.rotate_z(self.angle_of_periapsis())
+            .rotate_x(self.inclination())
+            .rotate_z(self.ascending_node())
+    }
+
+    /// Inertial unit vector toward the ascending node, `[cos Ω, sin Ω, 0]`. Equatorial orbits, prograde
+    /// or retrograde, have no node line and take the x axis, as in `from_state_vector`.
+    pub fn node_line(&self) -> Vector3 {
+        if self.inclination().sin().abs() < ECCENTRICITY_TOLERANCE {
+            return Vector3::i_hat();
+        }
+        let (s, c) = self.ascending_node().sin_cos();
+        Vector3::new(c, s, 0.0)
+    }
+
+    /// This orbit reflected across the xy plane, every point `(x, y, z)` taken to `(x, y, -z)` with the
+    /// same anomaly. A reflection keeps the sense of motion about the z axis, so the inclination is
+    /// unchanged and the opposite hemisphere is covered by turning the node and the periapsis half
+    /// a revolution: `Ω + π`, `ω + π`. The shape is untouched, and reflecting twice gives back the
+    /// original orbit.
+    pub fn mirror_equatorial(&self) -> Orbit {
+        let mirror = |v : Vector3| Vector3::new(v.x, v.y, -v.z);
+        // Angular momentum is a pseudovector: it reflects with the opposite sign
+        self.with_axes(-mirror(self.normal()), mirror(self.perifocal_to_inertial(Vector3::i_hat())))
+    }
+
+    /// Inertial state at the given anomaly. The reference plane is xy with the
+    /// ascending node measured from the x axis.
+    pub fn state_vector(&self, body : &Body, anomaly : &Anomaly) -> StateVector {
+        let (position, velocity) = state_from_elements(
+            body.k(),
+            self.parameter(),
+            self.eccentricity(),
+            self.inclination(),
+            self.ascending_node(),
+            self.angle_of_periapsis(),
+            anomaly.true_anomaly);
+        StateVector::new(position, velocity, anomaly.time_ms)
+    }
+
+    /// Partials of the inertial state `[x, y, z, vx, vy, vz]` (rows) with respect to
+    /// `[parameter, eccentricity, inclination, ascending_node, angle_of_periapsis, true_anomaly]` (columns),
+    /// so a covariance propagates as `J P Jᵀ`. The semi-latus rectum stands in for the semi-major
+    /// axis so the same layout holds for parabolic orbits.
+    pub fn element_jacobian(&self, body : &Body, anomaly : &Anomaly) -> [[f64; 6]; 6] {
+        let p = self.parameter();
+        let e = self.eccentricity();
+        let (s, c) = anomaly.true_anomaly.sin_cos();
+        let r = p / (1.0 + e * c);
+        let w = (body.k() / p).sqrt();
+        let to_inertial = |v : Vector3| self.perifocal_to_inertial(v);
+
+        let state = self.state_vector(body, anomaly);
+        let (position, velocity) = (state.position(), state.velocity());
+        let radial = Vector3::new(c, s, 0.0);
+        let node = Vector3::i_hat().rotate_z(self.ascending_node());
+        let normal = to_inertial(Vector3::k_hat());
+        let dr_dnu = r * e * s / (1.0 + e * c);
+
+        // Each orientation angle rotates the state about a fixed axis, so its partial is a cross product
+        let columns = [
+            (to_inertial(radial * (r / p)), velocity * (-0.5 / p)),
+            (to_inertial(radial * (-r * c / (1.0 + e * c))), to_inertial(Vector3::new(0.0, w, 0.0))),
+            (node.cross(position), node.cross(velocity)),
+            (Vector3::k_hat().cross(position), Vector3::k_hat().cross(velocity)),
+            (normal.cross(position), normal.cross(velocity)),
+            (to_inertial(Vector3::new(dr_dnu * c - r * s, dr_dnu * s + r * c, 0.0)), to_inertial(Vector3::new(-w * c, -w * s, 0.0))),
+        ];
+        jacobian_from_columns(&columns)
+    }
+
+    /// Central-difference counterpart to `element_jacobian`, for validating it
+    pub fn element_jacobian_numeric(&self, body : &Body, anomaly : &Anomaly) -> [[f64; 6]; 6] {
+        let elements = [
+            self.parameter(),
+            self.eccentricity(),
+            self.inclination(),
+            self.ascending_node(),
+            self.angle_of_periapsis(),
+            anomaly.true_anomaly
+        ];
+        let state = |x : &[f64; 6]| state_from_elements(body.k(), x[0], x[1], x[2], x[3], x[4], x[5]);
+
+        let mut columns = [(Vector3::zero(), Vector3::zero()); 6];
+        for (j, column) in columns.iter_mut().enumerate() {
+            let h = 1e-6 * elements[j].abs().max(1.0);
+            let mut ahead = elements;
+            let mut behind = elements;
+            ahead[j] += h;
+            behind[j] -= h;
+            let (r1, v1) = state(&ahead);
+            let (r0, v0) = state(&behind);
+            *column = ((r1 - r0) * (0.5 / h), (v1 - v0) * (0.5 / h));
+        }
+        jacobian_from_columns(&columns)
+    }
+
+    /// Radians/second. For parabolic orbits this is the rate of Barker's mean anomaly, sqrt(k / 2q^3)
+    pub fn mean_motion(&self, body : &Body) -> f64 {
+        match self.semimajor_axis() {
+            Some(a) => (body.k() / a.abs().powi(3)).sqrt(),
+            None => (body.k() / (2.0 * self.periapsis().powi(3))).sqrt()
+        }
+    }
+
+    /// Keplerian (sidereal) period in seconds, `2π / n`, None for open orbits. This is the
+    /// unperturbed two-body period; see `anomalistic_period` and `nodal_period` under J2.
+    pub fn orbital_period(&self, body : &Body) -> Option<f64> {
+        match *self {
+            Orbit::Circular { .. } | Orbit::Elliptical { .. } => Some(2.0 * PI / self.mean_motion(body)),
+            Orbit::Parabolic { .. } | Orbit::Hyperbolic { .. } => None
+        }
+    }
+
+    /// (eccentric, mean) anomaly corresponding to a true anomaly on this orbit
+    fn anomalies_from_true(&self, true_anomaly : f64) -> (f64, f64) {
+        match *self {
+            Orbit::Circular { .. } => (true_anomaly, true_anomaly),
+            Orbit::Elliptical { eccentricity, .. } => kepler::elliptical_from_true(eccentricity, true_anomaly),
+            Orbit::Parabolic { .. } => kepler::parabolic_from_true(true_anomaly),
+            Orbit::Hyperbolic { eccentricity, .. } => kepler::hyperbolic_from_true(eccentricity, true_anomaly)
+        }
+    }
+
+    /// (true, eccentric) anomaly corresponding to a mean anomaly on this orbit
+    fn anomalies_from_mean(&self, mean_anomaly : f64) -> (f64, f64) {
+        match *self {
+            Orbit::Circular { .. } => (mean_anomaly, mean_anomaly),
+            Orbit::Elliptical { eccentricity, .. } => kepler::elliptical_from_mean(eccentricity, mean_anomaly),
+            Orbit::Parabolic { .. } => kepler::parabolic_from_mean(mean_anomaly),
+            Orbit::Hyperbolic { eccentricity, .. } => kepler::hyperbolic_from_mean(eccentricity, mean_anomaly)
+        }
+    }
+
+    /// (true, mean) anomaly corresponding to an eccentric anomaly on this orbit
+    fn anomalies_from_eccentric(&self, eccentric_anomaly : f64) -> (f64, f64) {
+        match *self {
+            Orbit::Circular { .. } => (eccentric_anomaly, eccentric_anomaly),
+            Orbit::Elliptical { eccentricity, .. } => kepler::elliptical_from_eccentric(eccentricity, eccentric_anomaly),
+            Orbit::Parabolic { .. } => kepler::parabolic_from_barker(eccentric_anomaly),
+            Orbit::Hyperbolic { eccentricity, .. } => kepler::hyperbolic_from_hyperbolic(eccentricity, eccentric_anomaly)
+        }
+    }
+
+    /// Advances `start` along the orbit by `dt` seconds, backward when negative. The true anomaly of
+    /// `start` is taken as authoritative; mean and eccentric anomaly are rederived from it. Closed
+    /// orbits wrap into `[0, 2π)` either way, while open orbits simply run back through periapsis.
+    /// The clock can't go back past the Unix epoch: a result earlier than that has its `time_ms`
+    /// floored at 0 while the anomaly still moves the full `dt`. `try_next_anomaly` rejects it instead.
+    pub fn next_anomaly(&self, body : &Body, start : &Anomaly, dt : f64) -> Anomaly {
+        self.next_anomaly_with::<NewtonSolver>(body, start, dt)
+    }
+
+    /// `next_anomaly` inverting Kepler's equation with solver `S` on elliptical orbits. Circular,
+    /// parabolic, and hyperbolic orbits have their own closed forms or solvers and ignore `S`. Floors
+    /// `time_ms` at the Unix epoch, as `next_anomaly` does.
+    pub fn next_anomaly_with<S : KeplerSolver>(&self, body : &Body, start : &Anomaly, dt : f64) -> Anomaly {
+        let mean_anomaly = self.mean_anomaly_after(body, start, dt);
+        let time_ms = offset_time_ms(start.time_ms, dt);
+        match *self {
+            Orbit::Elliptical { eccentricity, .. } => {
+                let eccentric_anomaly = S::solve(eccentricity, mean_anomaly, kepler::TOLERANCE);
+                Anomaly::from_eccentric(self, eccentric_anomaly, time_ms)
+            },
+            _ => Anomaly::from_mean(self, mean_anomaly, time_ms)
+        }
+    }
+
+    /// `next_anomaly_with` to the tolerance and iteration cap in `config`, failing with
+    /// `OrbitError::NotConverged` rather than returning a partly converged anomaly. Hyperbolic orbits
+    /// are held to the same `config`; circular and parabolic ones have closed forms. Fails with
+    /// `OrbitError::Unreachable` when the result would fall before the Unix epoch, where `time_ms`
+    /// can't follow it.
+    pub fn try_next_anomaly<S : KeplerSolver>(&self, body : &Body, start : &Anomaly, dt : f64, config : &SolverConfig) -> Result<Anomaly, OrbitError> {
+        let time_ms = try_offset_time_ms(start.time_ms, dt)?;
+        let mean_anomaly = self.mean_anomaly_after(body, start, dt);
+        match *self {
+            Orbit::Elliptical { eccentricity, .. } => {
+                let eccentric_anomaly = S::solve_with(eccentricity, mean_anomaly, config)?;
+                Ok(Anomaly::from_eccentric(self, eccentric_anomaly, time_ms))
+            },
+            _ => Anomaly::try_from_mean(self, mean_anomaly, time_ms, config)
+        }
+    }
+
+    /// Mean anomaly `dt` seconds after `start`, wrapped into `[0, 2π)` on closed orbits
+    fn mean_anomaly_after(&self, body : &Body, start : &Anomaly, dt : f64) -> f64 {
+        let (_, start_mean) = self.anomalies_from_true(start.true_anomaly);
+        let mean_anomaly = start_mean + self.mean_motion(body) * dt;
+        if self.orbital_period(body).is_some() {
+            mean_anomaly.rem_euclid(2.0 * PI)
+        } else {
+            mean_anomaly
+        }
+    }
+
+    /// Mean anomaly `n·t` at `time_since_periapsis` seconds after a periapsis passage, wrapped into
+    /// `[0, 2π)` on closed orbits and left unbounded and signed on open ones
+    pub fn mean_anomaly_at_time(&self, body : &Body, time_since_periapsis : f64) -> f64 {
+        let mean_anomaly = self.mean_motion(body) * time_since_periapsis;
+        if self.orbital_period(body).is_some() {
+            mean_anomaly.rem_euclid(2.0 * PI)
+        } else {
+            mean_anomaly
+        }
+    }
+
+    /// Seconds since the last periapsis passage at `anomaly`, in `[0, period)` on closed orbits and
+    /// negative before periapsis on open ones. The inverse of `mean_anomaly_at_time`.
+    pub fn time_since_periapsis(&self, body : &Body, anomaly : &Anomaly) -> f64 {
+        let (_, mean_anomaly) = self.anomalies_from_true(anomaly.true_anomaly);
+        let mean_anomaly = if self.orbital_period(body).is_some() { mean_anomaly.rem_euclid(2.0 * PI) } else { mean_anomaly };
+        mean_anomaly / self.mean_motion(body)
+    }
+
+    /// Seconds from `current` until the mean anomaly next reaches `target_mean` radians, plus
+    /// `revolutions` whole periods more. Zero revolutions from the target itself is zero. The true
+    /// anomaly of `current` is taken as authoritative, as in `next_anomaly`.
+    /// `OrbitError::Unreachable` for open orbits, which never come back round.
+    pub fn time_to_mean_anomaly(&self, body : &Body, current : &Anomaly, target_mean : f64, revolutions : u32) -> Result<f64, OrbitError> {
+        let period = self.orbital_period(body)
+            .ok_or_else(|| OrbitError::Unreachable("an open orbit has no revolutions".to_string()))?;
+        let (_, current_mean) = self.anomalies_from_true(current.true_anomaly);
+        let sweep = (target_mean - current_mean).rem_euclid(2.0 * PI);
+        // A target a rounding error behind is where the orbit is now, not a revolution away
+        let sweep = if 2.0 * PI - sweep < kepler::TOLERANCE { 0.0 } else { sweep };
+        Ok(sweep / self.mean_motion(body) + revolutions as f64 * period)
+    }
+
+    /// Inertial position in meters a fraction `phase` of the period past periapsis, wrapped into
+    /// `[0, 1)` so an animation can feed it elapsed time over period directly. Errs with
+    /// `OrbitError::Unreachable` for open orbits, which have no period to take a fraction of.
+    pub fn position_at_phase(&self, body : &Body, phase : f64) -> Result<Vector3, OrbitError> {
+        self.orbital_period(body)
+            .ok_or_else(|| OrbitError::Unreachable("an open orbit has no period".to_string()))?;
+        let anomaly = Anomaly::from_mean(self, 2.0 * PI * phase.rem_euclid(1.0), 0);
+        Ok(self.position_at(body, anomaly.true_anomaly))
+    }
+
+    /// Days between the epoch of `anomaly` (its `time_ms`, from the Unix epoch) and `current_jd`
+    pub fn epoch_age_days(&self, anomaly : &Anomaly, current_jd : f64) -> f64 {
+        current_jd - (UNIX_EPOCH_JD + anomaly.time_ms as f64 / MS_PER_DAY)
+    }
+
+    /// `next_anomaly`, also flagging when the result lies more than `max_epoch_age_days` before or
+    /// after `epoch`, the anomaly the elements were fitted at. `start` may already be far from it;
+    /// the age is measured from the epoch, not from `start`. Elements fitted at an epoch (TLEs
+    /// especially) degrade quickly away from it, so a `true` flag means the result should not be
+    /// trusted. See `DEFAULT_MAX_EPOCH_AGE_DAYS`.
+    pub fn next_anomaly_checked(&self, body : &Body, epoch : &Anomaly, start : &Anomaly, dt : f64, max_epoch_age_days : f64) -> (Anomaly, bool) {
+        let next = self.next_anomaly(body, start, dt);
+        // From the unclamped time, so a result floored at the Unix epoch is still aged correctly
+        let current_jd = UNIX_EPOCH_JD + (start.time_ms as f64 + dt * 1000.0) / MS_PER_DAY;
+        let stale = self.epoch_age_days(epoch, current_jd).abs() > max_epoch_age_days;
+        (next, stale)
+    }
+
+    /// Seconds to travel from one true anomaly to another. Closed orbits always move forward,
+    /// wrapping through periapsis; open orbits give a negative time when `to` precedes `from`.
+    pub fn time_of_flight(&self, body : &Body, from_true_anomaly : f64, to_true_anomaly : f64) -> f64 {
+        let (_, from_mean) = self.anomalies_from_true(from_true_anomaly);
+        let (_, to_mean) = self.anomalies_from_true(to_true_anomaly);
+        let mut sweep = to_mean - from_mean;
+        if self.orbital_period(body).is_some() {
+            sweep = sweep.rem_euclid(2.0 * PI);
+        }
+        sweep / self.mean_motion(body)
+    }
+
+    /// True anomaly in `[0, π]` at which the orbit is `radius` meters from the body's center on the
+    /// way out from periapsis, the way in being its negative. None if the orbit never reaches that
+    /// radius, and for a circular orbit, which is at its radius everywhere or nowhere.
+    pub fn true_anomaly_at_radius(&self, radius : f64) -> Option<f64> {
+        let e = self.eccentricity();
+        if e == 0.0 || radius.is_nan() || radius <= 0.0 {
+            return None;
+        }
+        let cos_anomaly = (self.parameter() / radius - 1.0) / e;
+        if cos_anomaly.abs() > 1.0 { None } else { Some(cos_anomaly.acos()) }
+    }
+
+    /// Seconds from `anomaly` until the orbit next reaches `target_radius` meters from the body's
+    /// center, zero if it is there now. None if it never does again: the radius is out of range, an
+    /// open orbit is already outbound past it, or the orbit is circular at any other radius. A circular
+    /// orbit at its own radius, within `ECCENTRICITY_TOLERANCE` relative, is there now.
+    pub fn next_time_at_radius(&self, body : &Body, anomaly : &Anomaly, target_radius : f64) -> Option<f64> {
+        if let Orbit::Circular { semimajor_axis, .. } = *self {
+            let there = (target_radius - semimajor_axis).abs() <= ECCENTRICITY_TOLERANCE * semimajor_axis;
+            return if there { Some(0.0) } else { None };
+        }
+        let crossing = self.true_anomaly_at_radius(target_radius)?;
+        [crossing, -crossing].iter()
+            .map(|&to| self.time_of_flight(body, anomaly.true_anomaly, to))
+            .filter(|&t| t >= 0.0)
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    /// True anomaly in `(0, π)` where the orbit rises through the body's surface, the descent being its
+    /// negative. None if the periapsis is above the surface or the orbit never rises above it.
+    fn surface_crossing(&self, body : &Body) -> Option<f64> {
+        let e = self.eccentricity();
+        if !self.intersects_surface(body) || e == 0.0 {
+            return None;
+        }
+        let cos_surface = (self.parameter() / body.radius - 1.0) / e;
+        if cos_surface <= -1.0 { None } else { Some(cos_surface.acos()) }
+    }
+
+    /// True anomaly at which the orbit next descends through the body's surface after `launch_anomaly`,
+    /// None if the periapsis is above the surface or an open orbit is already outbound
+    fn impact_anomaly(&self, body : &Body, launch_anomaly : &Anomaly) -> Option<f64> {
+        if !self.intersects_surface(body) {
+            return None;
+        }
+        let surface = match self.surface_crossing(body) {
+            Some(surface) => surface,
+            // Entirely beneath the surface, so already impacting
+            None => return Some(launch_anomaly.true_anomaly)
+        };
+        match *self {
+            Orbit::Circular { .. } | Orbit::Elliptical { .. } => Some(2.0 * PI - surface),
+            Orbit::Parabolic { .. } | Orbit::Hyperbolic { .. } => {
+                let launch = (launch_anomaly.true_anomaly + PI).rem_euclid(2.0 * PI) - PI;
+                if launch < -surface { Some(-surface) } else { None }
+            }
+        }
+    }
+
+    fn position_at(&self, body : &Body, true_anomaly : f64) -> Vector3 {
+        self.state_vector(body, &Anomaly::from_true(self, true_anomaly, 0)).position()
+    }
+
+    /// Geocentric (latitude, longitude) in radians where the orbit comes back down through the body's
+    /// surface after launch, or None if it never does. Longitude is inertial, from the x axis.
+    pub fn impact_point(&self, body : &Body, launch_anomaly : &Anomaly) -> Option<(f64, f64)> {
+        self.impact_anomaly(body, launch_anomaly).map(|true_anomaly| {
+            let position = self.position_at(body, true_anomaly);
+            ((position.z / position.magnitude()).asin(), position.y.atan2(position.x))
+        })
+    }
+
+    /// (launch, impact) true anomalies of the ballistic arc from `launch_anomaly` down to the surface
+    fn ballistic_arc(&self, body : &Body, launch_anomaly : &Anomaly) -> Option<(f64, f64)> {
+        self.impact_anomaly(body, launch_anomaly).map(|impact| (launch_anomaly.true_anomaly, impact))
+    }
+
+    /// Seconds from `launch_anomaly` to impact, None if the orbit never comes down through the surface
+    pub fn ballistic_flight_time(&self, body : &Body, launch_anomaly : &Anomaly) -> Option<f64> {
+        self.ballistic_arc(body, launch_anomaly).map(|(launch, impact)| self.time_of_flight(body, launch, impact))
+    }
+
+    /// Great-circle surface distance in meters from the point under `launch_anomaly` to the impact
+    /// point, None if the orbit never comes down through the surface. Measured in inertial space, so
+    /// the body's rotation is ignored; see `ballistic_range_rotating`.
+    pub fn ballistic_range(&self, body : &Body, launch_anomaly : &Anomaly) -> Option<f64> {
+        self.surface_range(body, launch_anomaly, 0.0)
+    }
+
+    /// `ballistic_range` over a surface turning at the body's rotation rate: the impact point is taken
+    /// where the ground has turned to by the end of the flight, so eastward shots fall short. The same
+    /// as `ballistic_range` for a body without a rotation period.
+    pub fn ballistic_range_rotating(&self, body : &Body, launch_anomaly : &Anomaly) -> Option<f64> {
+        self.surface_range(body, launch_anomaly, body.rotation_rate())
+    }
+
+    fn surface_range(&self, body : &Body, launch_anomaly : &Anomaly, rotation_rate : f64) -> Option<f64> {
+        self.ballistic_arc(body, launch_anomaly).map(|(launch, impact)| {
+            let rotation = rotation_rate * self.time_of_flight(body, launch, impact);
+            let launch = self.position_at(body, launch);
+            let impact = self.position_at(body, impact).rotate_z(-rotation);
+            body.radius * launch.cross(impact).magnitude().atan2(launch.dot(impact))
+        })
+    }
+
+    // fn get_angle_of_descending_node(&self) -> f64 { *self.ascending_node + PI }
     // fn get_major_axis(&self) -> f64 { *self.periapsis + *self.apoapsis }
-    // fn get_semimajor_axis(&self) -> f64 { self.get_major_axis() / 2 }
 
-    // pub fn next_anomaly(&self, start : &Anomaly, time_delta_ms : f64) -> Anomaly {
-    //     panic!("Not implememented");
-    // }
-    
     // pub fn reference_frame_at_anomaly(&self, anomaly : &Anomaly) -> AffineMatrix {
     //     panic!("Not implememented");
     // }
 
-    // /// Distance moved 
+    // /// Distance moved
     // pub fn velocity_at_anomaly(&self, anomaly : &Anomaly) -> f64 {
     //     panic!("Not implememented");
     // }
 }
 
-struct Anomaly {
-    time_ms : u64,
-    true_anomaly : f64,
-    mean_anomaly : f64,    
-    eccentric_anomaly : f64
+pub struct Anomaly {
+    /// milliseconds since the Unix epoch
+    pub time_ms : u64,
+    /// radians
+    pub true_anomaly : f64,
+    /// radians, Barker's mean anomaly for parabolic orbits
+    pub mean_anomaly : f64,
+    /// radians, the hyperbolic anomaly for hyperbolic orbits and Barker's D for parabolic ones
+    pub eccentric_anomaly : f64
+}
+
+impl Anomaly {
+    /// Anomaly at a true anomaly (radians) on the orbit, with the other two derived to agree
+    pub fn from_true(orbit : &Orbit, true_anomaly : f64, time_ms : u64) -> Anomaly {
+        let (eccentric_anomaly, mean_anomaly) = orbit.anomalies_from_true(true_anomaly);
+        Anomaly { time_ms, true_anomaly, mean_anomaly, eccentric_anomaly }
+    }
+
+    /// Anomaly at a mean anomaly (radians) on the orbit, solving Kepler's equation for the others
+    pub fn from_mean(orbit : &Orbit, mean_anomaly : f64, time_ms : u64) -> Anomaly {
+        let (true_anomaly, eccentric_anomaly) = orbit.anomalies_from_mean(mean_anomaly);
+        Anomaly { time_ms, true_anomaly, mean_anomaly, eccentric_anomaly }
+    }
+
+    /// `from_mean` solving Kepler's equation to the tolerance and iteration cap in `config`, or
+    /// `OrbitError::NotConverged`
+    pub fn try_from_mean(orbit : &Orbit, mean_anomaly : f64, time_ms : u64, config : &SolverConfig) -> Result<Anomaly, OrbitError> {
+        let not_converged = |_| OrbitError::NotConverged { iterations : config.max_iterations };
+        let eccentric_anomaly = match *orbit {
+            Orbit::Elliptical { eccentricity, .. } => NewtonSolver::solve_with(eccentricity, mean_anomaly, config)?,
+            Orbit::Hyperbolic { eccentricity, .. } => kepler::solve_hyperbolic(eccentricity, mean_anomaly, config).map_err(not_converged)?,
+            Orbit::Circular { .. } | Orbit::Parabolic { .. } => return Ok(Anomaly::from_mean(orbit, mean_anomaly, time_ms))
+        };
+        let (true_anomaly, _) = orbit.anomalies_from_eccentric(eccentric_anomaly);
+        Ok(Anomaly { time_ms, true_anomaly, mean_anomaly, eccentric_anomaly })
+    }
+
+    /// Anomaly at a true longitude (radians, see `Orbit::true_longitude`), the way to place a body on a
+    /// circular or equatorial orbit whose ω or Ω carries no meaning of its own
+    pub fn from_true_longitude(orbit : &Orbit, true_longitude : f64, time_ms : u64) -> Anomaly {
+        let true_anomaly = (true_longitude - orbit.ascending_node() - orbit.angle_of_periapsis()).rem_euclid(2.0 * PI);
+        Anomaly::from_true(orbit, true_anomaly, time_ms)
+    }
+
+    /// Anomaly at an eccentric anomaly (radians) on the orbit, with the other two derived to agree
+    pub fn from_eccentric(orbit : &Orbit, eccentric_anomaly : f64, time_ms : u64) -> Anomaly {
+        let (true_anomaly, mean_anomaly) = orbit.anomalies_from_eccentric(eccentric_anomaly);
+        Anomaly { time_ms, true_anomaly, mean_anomaly, eccentric_anomaly }
+    }
+}
+
+/// `time_ms` moved by `dt` seconds, to the nearest millisecond and no earlier than the Unix epoch
+pub(crate) fn offset_time_ms(time_ms : u64, dt : f64) -> u64 {
+    (time_ms as f64 + dt * 1000.0).round().max(0.0) as u64
+}
+
+/// `offset_time_ms`, failing with `OrbitError::Unreachable` rather than stopping at the Unix epoch
+pub(crate) fn try_offset_time_ms(time_ms : u64, dt : f64) -> Result<u64, OrbitError> {
+    if (time_ms as f64 + dt * 1000.0).round() < 0.0 {
+        return Err(OrbitError::Unreachable(format!("{} s from {} ms is before the Unix epoch", dt, time_ms)));
+    }
+    Ok(offset_time_ms(time_ms, dt))
+}
+
+/// Orbit and true anomaly through an inertial state
+fn elements_from_state(position : Vector3, velocity : Vector3, k : f64) -> (Orbit, f64) {
+    let h = position.cross(velocity);
+    let normal = h.unit();
+    let r = position.magnitude();
+    let eccentricity_vector = (position * (velocity.magnitude_squared() - k / r) - velocity * position.dot(velocity)) * (1.0 / k);
+    let eccentricity = eccentricity_vector.magnitude();
+    let parameter = h.magnitude_squared() / k;
+
+    let inclination = normal.z.clamp(-1.0, 1.0).acos();
+    let node_vector = Vector3::k_hat().cross(h);
+    let (ascending_node, node) = if node_vector.magnitude() > ECCENTRICITY_TOLERANCE * h.magnitude() {
+        (node_vector.y.atan2(node_vector.x).rem_euclid(2.0 * PI), node_vector.unit())
+    } else {
+        (0.0, Vector3::i_hat())
+    };
+
+    // Signed angle from `from` to `to`, positive in the direction of motion
+    let angle = |from : Vector3, to : Vector3| from.cross(to).dot(normal).atan2(from.dot(to));
+    let (angle_of_periapsis, true_anomaly) = if eccentricity > ECCENTRICITY_TOLERANCE {
+        (angle(node, eccentricity_vector).rem_euclid(2.0 * PI), angle(eccentricity_vector, position))
+    } else {
+        (0.0, angle(node, position))
+    };
+    let true_anomaly = if eccentricity < 1.0 { true_anomaly.rem_euclid(2.0 * PI) } else { true_anomaly };
+
+    let orbit = Orbit::from_elements(parameter / (1.0 + eccentricity), eccentricity, inclination, ascending_node, angle_of_periapsis);
+    (orbit, true_anomaly)
+}
+
+/// Inertial state from raw elements, without requiring them to form a valid `Orbit` variant
+fn state_from_elements(
+    k : f64,
+    parameter : f64,
+    eccentricity : f64,
+    inclination : f64,
+    ascending_node : f64,
+    angle_of_periapsis : f64,
+    true_anomaly : f64) -> (Vector3, Vector3) {
+    let (s, c) = true_anomaly.sin_cos();
+    let r = parameter / (1.0 + eccentricity * c);
+    let w = (k / parameter).sqrt();
+    let to_inertial = |v : Vector3| v
+        .rotate_z(angle_of_periapsis)
+        .rotate_x(inclination)
+        .rotate_z(ascending_node);
+    (
+        to_inertial(Vector3::new(r * c, r * s, 0.0)),
+        to_inertial(Vector3::new(-w * s, w * (eccentricity + c), 0.0))
+    )
+}
+
+fn jacobian_from_columns(columns : &[(Vector3, Vector3); 6]) -> [[f64; 6]; 6] {
+    let mut jacobian = [[0.0; 6]; 6];
+    for (j, (dr, dv)) in columns.iter().enumerate() {
+        let partials = [dr.x, dr.y, dr.z, dv.x, dv.y, dv.z];
+        for (row, partial) in jacobian.iter_mut().zip(partials.iter()) {
+            row[j] = *partial;
+        }
+    }
+    jacobian
+}
+
+/// The point-mass Earth most tests orbit
+#[cfg(test)]
+pub(crate) fn earth() -> Body {
+    Body::builder().mass(5.972e24).radius(6.371e6).build().unwrap()
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    fn at(true_anomaly : f64) -> Anomaly {
+        Anomaly { time_ms : 0, true_anomaly, mean_anomaly : 0.0, eccentric_anomaly : 0.0 }
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn circular_state_vector_speed() {
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, semimajor_axis : 7.0e6 };
+        let state = orbit.state_vector(&earth(), &at(2.0));
+        assert!((state.radius() - 7.0e6).abs() < 1e-6);
+        assert!((state.speed() - (earth().k() / 7.0e6).sqrt()).abs() < 1e-9);
+        assert!(state.position().dot(state.velocity()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn element_jacobian_matches_finite_difference() {
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 };
+        let anomaly = at(1.1);
+        let analytic = orbit.element_jacobian(&earth(), &anomaly);
+        let numeric = orbit.element_jacobian_numeric(&earth(), &anomaly);
+        for (a_row, n_row) in analytic.iter().zip(numeric.iter()) {
+            for (a, n) in a_row.iter().zip(n_row.iter()) {
+                assert!((a - n).abs() <= 1e-5 * a.abs().max(1.0), "{} vs {}", a, n);
+            }
+        }
+    }
+
+    #[test]
+    fn next_anomaly_round_trips_over_period() {
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.6 };
+        let period = orbit.orbital_period(&earth()).unwrap();
+        let quarter = orbit.next_anomaly(&earth(), &at(0.4), period / 4.0);
+        let full = orbit.next_anomaly(&earth(), &at(0.4), period);
+        assert!(quarter.true_anomaly > 0.4);
+        assert!((full.true_anomaly - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn next_anomaly_runs_backward() {
+        let body = earth();
+        let start = Anomaly::from_true(&Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 }, 0.0, 10_000_000);
+        let orbits = [
+            Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.6 },
+            Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 1.5 }
+        ];
+        for orbit in orbits.iter() {
+            let start = Anomaly::from_true(orbit, 0.3, start.time_ms);
+            let earlier = orbit.next_anomaly(&body, &start, -1200.0);
+            assert_eq!(earlier.time_ms, 8_800_000);
+            let back = orbit.next_anomaly(&body, &earlier, 1200.0);
+            assert!((back.true_anomaly - 0.3).abs() < 1e-9);
+            assert_eq!(back.time_ms, start.time_ms);
+        }
+    }
+
+    #[test]
+    fn open_orbits_propagate_outbound() {
+        let hyperbolic = Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 1.5 };
+        let parabolic = Orbit::Parabolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6 };
+        for orbit in [hyperbolic, parabolic].iter() {
+            let next = orbit.next_anomaly(&earth(), &at(0.5), 3600.0);
+            let (eccentric, mean) = orbit.anomalies_from_true(next.true_anomaly);
+            assert!(next.true_anomaly > 0.5);
+            assert!((mean - next.mean_anomaly).abs() < 1e-9);
+            assert!((eccentric - next.eccentric_anomaly).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn state_vector_round_trip() {
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 };
+        let state = orbit.state_vector(&earth(), &at(1.1));
+        let (rebuilt, anomaly) = Orbit::from_burnout(&state, &earth()).unwrap();
+        assert!((rebuilt.periapsis() - 7.0e6).abs() < 1e-3);
+        assert!((rebuilt.eccentricity() - 0.3).abs() < 1e-9);
+        assert!((rebuilt.inclination() - 0.9).abs() < 1e-9);
+        assert!((rebuilt.ascending_node() - 2.1).abs() < 1e-9);
+        assert!((rebuilt.angle_of_periapsis() - 0.7).abs() < 1e-9);
+        assert!((anomaly.true_anomaly - 1.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn retrograde_state_vector_round_trip() {
+        let orbits = [
+            Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 0.0, inclination : PI, periapsis : 7.0e6, eccentricity : 0.3 },
+            Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 2.5, periapsis : 7.0e6, eccentricity : 0.3 },
+        ];
+        for orbit in orbits.iter() {
+            let state = orbit.state_vector(&earth(), &at(1.1));
+            assert!(state.position().cross(state.velocity()).z < 0.0);
+            let (rebuilt, anomaly) = Orbit::from_burnout(&state, &earth()).unwrap();
+            assert!((rebuilt.inclination() - orbit.inclination()).abs() < 1e-9);
+            assert!((rebuilt.ascending_node() - orbit.ascending_node()).abs() < 1e-9);
+            assert!((rebuilt.angle_of_periapsis() - 0.7).abs() < 1e-9);
+            assert!((anomaly.true_anomaly - 1.1).abs() < 1e-9);
+        }
+
+        let circular = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : PI, semimajor_axis : 7.0e6 };
+        let (rebuilt, anomaly) = Orbit::from_burnout(&circular.state_vector(&earth(), &at(1.1)), &earth()).unwrap();
+        assert!((rebuilt.inclination() - PI).abs() < 1e-9);
+        assert!((anomaly.true_anomaly - 1.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn conserved_quantities_round_trip() {
+        let body = earth();
+        let orbits = [
+            Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 },
+            Orbit::Parabolic { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6 },
+            Orbit::Hyperbolic { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 1.4 },
+        ];
+        for orbit in orbits.iter() {
+            let rebuilt = Orbit::from_energy_and_momentum(orbit.total_energy(&body), orbit.angular_momentum(&body), &body, 0.9, 2.1, 0.7, true);
+            assert_eq!(std::mem::discriminant(&rebuilt), std::mem::discriminant(orbit));
+            assert!((rebuilt.periapsis() - 7.0e6).abs() < 1e-3);
+            assert!((rebuilt.eccentricity() - orbit.eccentricity()).abs() < 1e-9);
+
+            // The other way round: same periapsis point, velocity reversed
+            let reversed = Orbit::from_energy_and_momentum(orbit.total_energy(&body), orbit.angular_momentum(&body), &body, 0.9, 2.1, 0.7, false);
+            assert!((reversed.inclination() - (PI - 0.9)).abs() < 1e-12);
+            let forward = rebuilt.state_vector(&body, &Anomaly::from_true(&rebuilt, 0.0, 0));
+            let backward = reversed.state_vector(&body, &Anomaly::from_true(&reversed, 0.0, 0));
+            assert!((forward.position() - backward.position()).magnitude() < 1e-3);
+            assert!((forward.velocity() + backward.velocity()).magnitude() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn true_anomaly_rate_integrates_to_a_revolution() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.5 };
+        let steps = 20_000;
+        let dt = orbit.orbital_period(&body).unwrap() / steps as f64;
+        let swept : f64 = (0..steps)
+            .map(|i| Anomaly::from_mean(&orbit, 2.0 * PI * (i as f64 + 0.5) / steps as f64, 0))
+            .map(|anomaly| orbit.true_anomaly_rate(&body, &anomaly) * dt)
+            .sum();
+        assert!((swept - 2.0 * PI).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ballistic_impact_point() {
+        let body = earth();
+        let burnout = StateVector::new(Vector3::new(body.radius, 0.0, 0.0), Vector3::new(3000.0, 3000.0, 0.0), 0);
+        let (orbit, launch) = Orbit::from_burnout(&burnout, &body).unwrap();
+        let (latitude, longitude) = orbit.impact_point(&body, &launch).unwrap();
+        assert!(latitude.abs() < 1e-9);
+        assert!(longitude > 0.0);
+
+        let high = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        assert!(high.impact_point(&body, &at(0.0)).is_none());
+    }
+
+    #[test]
+    fn ballistic_range_and_flight_time() {
+        let mut body = earth();
+        let burnout = StateVector::new(Vector3::new(body.radius, 0.0, 0.0), Vector3::new(3000.0, 3000.0, 0.0), 0);
+        let (orbit, launch) = Orbit::from_burnout(&burnout, &body).unwrap();
+        let (_, longitude) = orbit.impact_point(&body, &launch).unwrap();
+        let still = orbit.ballistic_range(&body, &launch).unwrap();
+        assert!((still - body.radius * longitude).abs() < 1e-3);
+        let flight_time = orbit.ballistic_flight_time(&body, &launch).unwrap();
+        let descent = 2.0 * PI - launch.true_anomaly;
+        assert!((orbit.time_of_flight(&body, launch.true_anomaly, descent) - flight_time).abs() < 1e-6);
+        assert_eq!(orbit.ballistic_range_rotating(&body, &launch), Some(still));
+
+        body.rotation_period = Some(86_164.1);
+        assert_eq!(orbit.ballistic_range(&body, &launch), Some(still));
+        let rotating = orbit.ballistic_range_rotating(&body, &launch).unwrap();
+        assert!((still - rotating - body.radius * body.rotation_rate() * flight_time).abs() < 1e-3);
+
+        // From partway up the ascent the rest of the arc is shorter in both distance and time
+        let midway = Anomaly::from_true(&orbit, (launch.true_anomaly + PI) / 2.0, 0);
+        assert!(orbit.ballistic_range(&body, &midway).unwrap() < still);
+        assert!(orbit.ballistic_flight_time(&body, &midway).unwrap() < flight_time);
+
+        let high = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        assert!(high.ballistic_range(&body, &at(0.0)).is_none());
+    }
+
+    #[test]
+    fn altitudes_above_surface() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 6.771e6, eccentricity : 0.1 };
+        assert!((orbit.periapsis_altitude(&body) - 4.0e5).abs() < 1e-6);
+        assert!((orbit.altitude(&body, &at(PI)) - orbit.apoapsis_altitude(&body).unwrap()).abs() < 1e-6);
+        let escape = Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 6.771e6, eccentricity : 1.1 };
+        assert!(escape.apoapsis_altitude(&body).is_none());
+    }
+
+    #[test]
+    fn oblate_altitude() {
+        let mut body = earth();
+        body.flattening = 1.0 / 298.257;
+        let polar = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : PI / 2.0, semimajor_axis : 7.0e6 };
+        assert!((body.geocentric_radius(0.0) - body.radius).abs() < 1e-6);
+        assert!((body.geocentric_radius(PI / 2.0) - body.radius * (1.0 - body.flattening)).abs() < 1e-6);
+        let over_pole = polar.altitude(&body, &at(PI / 2.0));
+        let over_equator = polar.altitude(&body, &at(0.0));
+        assert!((over_pole - over_equator - body.radius * body.flattening).abs() < 1e-6);
+    }
+
+    #[test]
+    fn surface_intersection() {
+        let body = earth();
+        let decayed = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 6.3e6, eccentricity : 0.2 };
+        let clear = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 6.771e6 };
+        assert!(decayed.intersects_surface(&body));
+        assert!(!clear.intersects_surface(&body));
+    }
+
+    #[test]
+    fn anomaly_constructors_agree() {
+        let orbits = [
+            Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.5 },
+            Orbit::Parabolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6 },
+            Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 2.0 },
+        ];
+        for orbit in orbits.iter() {
+            let reference = Anomaly::from_true(orbit, 1.3, 5);
+            for anomaly in [
+                Anomaly::from_mean(orbit, reference.mean_anomaly, 5),
+                Anomaly::from_eccentric(orbit, reference.eccentric_anomaly, 5)
+            ].iter() {
+                assert!((anomaly.true_anomaly - reference.true_anomaly).abs() < 1e-9);
+                assert!((anomaly.mean_anomaly - reference.mean_anomaly).abs() < 1e-9);
+                assert!((anomaly.eccentric_anomaly - reference.eccentric_anomaly).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn stale_epoch_is_flagged() {
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        let epoch = at(0.0);
+        assert!((orbit.epoch_age_days(&epoch, UNIX_EPOCH_JD + 20.0) - 20.0).abs() < 1e-9);
+        let (_, fresh) = orbit.next_anomaly_checked(&earth(), &epoch, &epoch, 86_400.0, DEFAULT_MAX_EPOCH_AGE_DAYS);
+        let (_, stale) = orbit.next_anomaly_checked(&earth(), &epoch, &epoch, -15.0 * 86_400.0, DEFAULT_MAX_EPOCH_AGE_DAYS);
+        assert!(!fresh);
+        assert!(stale);
+
+        // Two ten-day steps from the epoch: the second lands twenty days out, though it only moved ten
+        let (ten_days, fresh) = orbit.next_anomaly_checked(&earth(), &epoch, &epoch, 10.0 * 86_400.0, DEFAULT_MAX_EPOCH_AGE_DAYS);
+        let (_, stale) = orbit.next_anomaly_checked(&earth(), &epoch, &ten_days, 10.0 * 86_400.0, DEFAULT_MAX_EPOCH_AGE_DAYS);
+        assert!(!fresh);
+        assert!(stale);
+    }
+
+    #[test]
+    fn true_longitude_survives_degenerate_orbits() {
+        let body = earth();
+        // Circular and equatorial: state vectors pin down only the true longitude
+        let orbit = Orbit::Circular { angle_of_periapsis : 1.0, ascending_node : 2.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        let anomaly = Anomaly::from_true_longitude(&orbit, 0.5, 0);
+        assert!((orbit.true_longitude(&anomaly) - 0.5).abs() < 1e-12);
+        let state = orbit.state_vector(&body, &anomaly);
+        assert!((state.position().y.atan2(state.position().x) - 0.5).abs() < 1e-9);
+        let (refit, refit_anomaly) = elements_from_state(state.position(), state.velocity(), body.k());
+        assert!((refit.true_longitude(&Anomaly::from_true(&refit, refit_anomaly, 0)) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn kinematics_match_the_individual_methods() {
+        let body = earth();
+        let orbit = Orbit::Hyperbolic { angle_of_periapsis : 0.2, ascending_node : 0.0, inclination : 0.1, periapsis : 7.0e6, eccentricity : 1.4 };
+        for &nu in [0.0, 1.0, -1.5].iter() {
+            let (radius, speed, flight_path_angle) = orbit.kinematics(&body, &at(nu));
+            let state = orbit.state_vector(&body, &at(nu));
+            assert!((radius - orbit.distance_from_parent(&at(nu))).abs() < 1e-6);
+            assert!((speed - orbit.vis_viva(&body, radius)).abs() < 1e-9);
+            assert!((flight_path_angle - orbit.flight_path_angle(&at(nu))).abs() < 1e-15);
+            let climb = state.position().unit().dot(state.velocity().unit()).asin();
+            assert!((flight_path_angle - climb).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn mean_motion_round_trips() {
+        let body = earth();
+        for &e in [0.0, 0.3, 1.0, 1.8].iter() {
+            let n = 15.5 * REVOLUTIONS_PER_DAY;
+            let orbit = Orbit::from_mean_motion(&body, n, e, 0.9, 1.0, 2.0);
+            assert!((orbit.mean_motion(&body) / n - 1.0).abs() < 1e-12);
+            assert!((orbit.eccentricity() - e).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn longitudes_wrap_into_a_turn() {
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 5.0, ascending_node : 4.0, inclination : 0.5, periapsis : 7.0e6, eccentricity : 0.2 };
+        assert!((orbit.longitude_of_periapsis() - (9.0 - 2.0 * PI)).abs() < 1e-12);
+        let anomaly = Anomaly::from_mean(&orbit, 6.0, 0);
+        assert!((orbit.mean_longitude(&anomaly) - (15.0 - 4.0 * PI)).abs() < 1e-12);
+        let nodeless = Orbit::Circular { angle_of_periapsis : 1.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        assert_eq!(nodeless.longitude_of_periapsis(), 1.0);
+    }
+
+    #[test]
+    fn period_round_trips() {
+        let body = earth();
+        for &(period, e) in [(5400.0, 0.0), (86_164.1, 0.001), (43_082.0, 0.74)].iter() {
+            let orbit = Orbit::from_period(&body, period, e, 0.9, 1.0, 2.0).ok().unwrap();
+            assert!((orbit.orbital_period(&body).unwrap() / period - 1.0).abs() < 1e-12);
+            assert!((orbit.eccentricity() - e).abs() < 1e-12);
+        }
+        assert!(Orbit::from_period(&body, 0.0, 0.1, 0.0, 0.0, 0.0).is_err());
+        assert!(Orbit::from_period(&body, -5400.0, 0.1, 0.0, 0.0, 0.0).is_err());
+        assert!(Orbit::from_period(&body, 5400.0, 1.2, 0.0, 0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn scalar_kinematics_match_the_state_vector() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.4, ascending_node : 1.1, inclination : 0.6, periapsis : 6.8e6, eccentricity : 0.2 };
+        let anomaly = at(2.0);
+        let (radius, speed, flight_path_angle) = orbit.kinematics(&body, &anomaly);
+        let rebuilt = Orbit::from_radius_velocity_fpa(radius, speed, flight_path_angle, &body, 0.6, 1.1, 2.4);
+        assert!((rebuilt.periapsis() - 6.8e6).abs() < 1e-3);
+        assert!((rebuilt.eccentricity() - 0.2).abs() < 1e-12);
+        assert!((rebuilt.angle_of_periapsis() - 0.4).abs() < 1e-9);
+        // Horizontal at circular speed
+        let circular = Orbit::from_radius_velocity_fpa(7.0e6, (body.k() / 7.0e6).sqrt(), 0.0, &body, 0.0, 0.0, 1.0);
+        assert!(matches!(circular, Orbit::Circular { .. }));
+        let escaping = Orbit::from_radius_velocity_fpa(7.0e6, 1.5 * (2.0 * body.k() / 7.0e6).sqrt(), 0.3, &body, 0.0, 0.0, 1.0);
+        assert!(matches!(escaping, Orbit::Hyperbolic { .. }));
+    }
+
+    #[test]
+    fn speed_at_altitude_stays_between_the_apsides() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.2 };
+        let apoapsis = orbit.apoapsis().unwrap();
+        assert_eq!(orbit.speed_at_altitude(&body, 7.0e6 - body.radius), Some(orbit.vis_viva(&body, 7.0e6)));
+        assert!(orbit.speed_at_altitude(&body, apoapsis - body.radius).is_some());
+        assert!(orbit.speed_at_altitude(&body, 6.9e6 - body.radius).is_none());
+        assert!(orbit.speed_at_altitude(&body, apoapsis + 1.0 - body.radius).is_none());
+        let circular = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.1e6 };
+        assert!(circular.speed_at_altitude(&body, 7.1e6 - body.radius).is_some());
+        let hyperbolic = Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 2.0 };
+        assert!(hyperbolic.speed_at_altitude(&body, 1.0e9).is_some());
+    }
+
+    #[test]
+    fn capped_propagation_fails_instead_of_half_converging() {
+        let body = earth();
+        let config = SolverConfig { tolerance : 1e-12, max_iterations : 1 };
+        let elliptical = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.9 };
+        let hyperbolic = Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 3.0 };
+        for orbit in [elliptical, hyperbolic].iter() {
+            let start = Anomaly::from_true(orbit, 0.3, 0);
+            assert_eq!(orbit.try_next_anomaly::<NewtonSolver>(&body, &start, 5000.0, &config).err(), Some(OrbitError::NotConverged { iterations : 1 }));
+            let solved = orbit.try_next_anomaly::<NewtonSolver>(&body, &start, 5000.0, &SolverConfig::default()).ok().unwrap();
+            let expected = orbit.next_anomaly(&body, &start, 5000.0);
+            assert!((solved.true_anomaly - expected.true_anomaly).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn node_line_is_where_the_orbit_climbs_through_the_plane() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.4, ascending_node : 2.5, inclination : 0.7, periapsis : 7.0e6, eccentricity : 0.2 };
+        let at_node = orbit.state_vector(&body, &Anomaly::from_true(&orbit, -0.4, 0));
+        assert!((at_node.position().unit() - orbit.node_line()).magnitude() < 1e-12);
+        assert!(at_node.velocity().z > 0.0);
+        assert!(orbit.node_line().dot(orbit.normal()).abs() < 1e-15);
+        let retrograde = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 2.5, inclination : PI, semimajor_axis : 7.0e6 };
+        assert_eq!(retrograde.node_line(), Vector3::i_hat());
+    }
+
+    #[test]
+    fn mirror_equatorial_reflects_every_point() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.4, ascending_node : 2.5, inclination : 0.7, periapsis : 7.0e6, eccentricity : 0.2 };
+        let mirrored = orbit.mirror_equatorial();
+        assert!((mirrored.inclination() - 0.7).abs() < 1e-12);
+        assert!((mirrored.ascending_node() - (2.5 + PI)).abs() < 1e-12);
+        assert!((mirrored.angle_of_periapsis() - (0.4 + PI)).abs() < 1e-12);
+        assert_eq!((mirrored.periapsis(), mirrored.eccentricity()), (orbit.periapsis(), orbit.eccentricity()));
+        for &nu in [0.0, 1.0, 2.5].iter() {
+            let (original, reflected) = (orbit.state_vector(&body, &at(nu)), mirrored.state_vector(&body, &at(nu)));
+            let flip = |v : Vector3| Vector3::new(v.x, v.y, -v.z);
+            assert!((flip(original.position()) - reflected.position()).magnitude() < 1e-6);
+            assert!((flip(original.velocity()) - reflected.velocity()).magnitude() < 1e-9);
+        }
+        let twice = mirrored.mirror_equatorial();
+        assert!((twice.ascending_node() - 2.5).abs() < 1e-12 && (twice.angle_of_periapsis() - 0.4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn next_radius_crossing_is_in_the_future() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 0.2 };
+        let crossing = orbit.true_anomaly_at_radius(8.0e6).unwrap();
+        // Inbound past the crossing: the next one is on the way out again, after periapsis
+        let start = Anomaly::from_true(&orbit, -0.5 * crossing, 0);
+        let t = orbit.next_time_at_radius(&body, &start, 8.0e6).unwrap();
+        assert!((t - orbit.time_of_flight(&body, -0.5 * crossing, crossing)).abs() < 1e-9);
+        let there = orbit.state_vector(&body, &orbit.next_anomaly(&body, &start, t));
+        assert!((there.radius() - 8.0e6).abs() < 1e-3);
+        assert!(orbit.next_time_at_radius(&body, &start, 2.0e7).is_none());
+
+        let escape = Orbit::Hyperbolic { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 1.5 };
+        let outbound = Anomaly::from_true(&escape, 1.0, 0);
+        assert!(escape.next_time_at_radius(&body, &outbound, 8.0e6).is_none());
+        assert!(escape.next_time_at_radius(&body, &outbound, 5.0e7).unwrap() > 0.0);
+    }
+
+    #[test]
+    fn apsis_kinematics_conserve_angular_momentum() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 0.2 };
+        let ((r_p, v_p), apoapsis) = orbit.apsis_kinematics(&body);
+        let (r_a, v_a) = apoapsis.unwrap();
+        assert_eq!((r_p, r_a), (7.0e6, orbit.apoapsis().unwrap()));
+        assert!((r_p * v_p - r_a * v_a).abs() < 1e-6 * r_p * v_p);
+        assert!((r_p * v_p - orbit.angular_momentum(&body)).abs() < 1e-6 * r_p * v_p);
+
+        let escape = Orbit::Hyperbolic { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 1.5 };
+        let ((_, speed), apoapsis) = escape.apsis_kinematics(&body);
+        assert!(apoapsis.is_none());
+        assert!(speed > body.escape_velocity(7.0e6));
+    }
+
+    #[test]
+    fn parabolic_comet_uses_the_periapsis_form() {
+        // A long-period comet on its parabolic approximation, perihelion 0.5 AU
+        let sun = Body::builder().mass(1.989e30).radius(6.957e8).build().unwrap();
+        let q = 0.5 * 1.496e11;
+        let comet = Orbit::Parabolic { angle_of_periapsis : 2.0, ascending_node : 1.0, inclination : 2.5, periapsis : q };
+        assert!(comet.semimajor_axis().is_none() && comet.apoapsis().is_none() && comet.orbital_period(&sun).is_none());
+        assert_eq!(comet.total_energy(&sun), 0.0);
+        assert_eq!(comet.parameter(), 2.0 * q);
+        assert!((comet.mean_motion(&sun) - (sun.k() / (2.0 * q.powi(3))).sqrt()).abs() < 1e-24);
+        for &nu in [-2.5, -1.0, 0.0, 0.7, 2.9].iter() {
+            let anomaly = Anomaly::from_true(&comet, nu, 0);
+            let r = comet.distance_from_parent(&anomaly);
+            assert!((r - 2.0 * q / (1.0 + nu.cos())).abs() < 1e-6 * r);
+            // Exactly escape speed everywhere, zero energy from the state vector too
+            assert!((comet.vis_viva(&sun, r) - sun.escape_velocity(r)).abs() < 1e-9);
+            let state = comet.state_vector(&sun, &anomaly);
+            assert!((state.radius() - r).abs() < 1e-6 * r);
+            assert!((state.speed() - sun.escape_velocity(r)).abs() < 1e-6);
+            assert!((state.speed().powi(2) / 2.0 - sun.k() / r).abs() < 1e-9 * sun.k() / r);
+        }
+    }
+
+    #[test]
+    fn mean_anomaly_at_time_inverts_time_since_periapsis() {
+        let body = earth();
+        let elliptical = Orbit::Elliptical { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 0.2 };
+        let period = elliptical.orbital_period(&body).unwrap();
+        for &nu in [0.0, 1.0, 3.0, -1.0].iter() {
+            let anomaly = Anomaly::from_true(&elliptical, nu, 0);
+            let t = elliptical.time_since_periapsis(&body, &anomaly);
+            assert!((0.0..period).contains(&t));
+            let (_, mean) = elliptical.anomalies_from_true(nu);
+            assert!(maneuvers::wrap_angle(elliptical.mean_anomaly_at_time(&body, t) - mean).abs() < 1e-12);
+        }
+        assert!((elliptical.mean_anomaly_at_time(&body, 2.5 * period) - PI).abs() < 1e-9);
+
+        let hyperbolic = Orbit::Hyperbolic { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 1.5 };
+        let inbound = Anomaly::from_true(&hyperbolic, -1.5, 0);
+        let t = hyperbolic.time_since_periapsis(&body, &inbound);
+        assert!(t < 0.0);
+        assert!((hyperbolic.mean_anomaly_at_time(&body, t) - inbound.mean_anomaly).abs() < 1e-9);
+        assert!(hyperbolic.mean_anomaly_at_time(&body, 1.0e6) > 2.0 * PI);
+    }
+
+    #[test]
+    fn pointing_directions_at_periapsis() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 0.2 };
+        let periapsis = Anomaly::from_true(&orbit, 0.0, 0);
+        let (ram, nadir) = (orbit.velocity_direction(&body, &periapsis), orbit.nadir_direction(&body, &periapsis));
+        assert!((ram.magnitude() - 1.0).abs() < 1e-12 && (nadir.magnitude() - 1.0).abs() < 1e-12);
+        // Velocity is horizontal at an apsis, square to nadir and in the orbit plane
+        assert!(ram.dot(nadir).abs() < 1e-12 && ram.dot(orbit.normal()).abs() < 1e-12);
+        assert!((nadir + orbit.perifocal_to_inertial(Vector3::i_hat())).magnitude() < 1e-12);
+        let climbing = Anomaly::from_true(&orbit, 1.0, 0);
+        assert!(orbit.velocity_direction(&body, &climbing).dot(orbit.nadir_direction(&body, &climbing)) < 0.0);
+    }
+
+    #[test]
+    fn time_to_mean_anomaly_wraps_and_adds_revolutions() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 0.2 };
+        let (n, period) = (orbit.mean_motion(&body), orbit.orbital_period(&body).unwrap());
+        let current = Anomaly::from_mean(&orbit, 6.0, 0);
+        // Across 2π: from 6.0 to 0.5 is 0.5 + 2π - 6.0 radians
+        let t = orbit.time_to_mean_anomaly(&body, &current, 0.5, 0).unwrap();
+        assert!((t - (0.5 + 2.0 * PI - 6.0) / n).abs() < 1e-6);
+        let later = orbit.time_to_mean_anomaly(&body, &current, 0.5, 3).unwrap();
+        assert!((later - t - 3.0 * period).abs() < 1e-6);
+        assert!((orbit.next_anomaly(&body, &current, later).mean_anomaly - 0.5).abs() < 1e-9);
+        assert!(orbit.time_to_mean_anomaly(&body, &current, 6.0, 0).unwrap().abs() < 1e-6);
+
+        let escape = Orbit::Hyperbolic { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 1.5 };
+        assert!(escape.time_to_mean_anomaly(&body, &at(0.1), 1.0, 0).is_err());
+    }
+
+    #[test]
+    fn phase_walks_the_period_from_periapsis() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 0.2 };
+        let periapsis = orbit.position_at_phase(&body, 0.0).ok().unwrap();
+        assert!((periapsis.magnitude() - 7.0e6).abs() < 1e-3);
+        assert!((orbit.position_at_phase(&body, 1.0).ok().unwrap() - periapsis).magnitude() < 1e-3);
+        let apoapsis = orbit.position_at_phase(&body, 0.5).ok().unwrap();
+        assert!((apoapsis.magnitude() - orbit.apoapsis().unwrap()).abs() < 1e-3);
+        let quarter = orbit.next_anomaly(&body, &at(0.0), orbit.orbital_period(&body).unwrap() / 4.0);
+        let expected = orbit.state_vector(&body, &quarter).position();
+        assert!((orbit.position_at_phase(&body, 1.25).ok().unwrap() - expected).magnitude() < 1e-3);
+
+        let escape = Orbit::Hyperbolic { angle_of_periapsis : 0.3, ascending_node : 1.2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 1.5 };
+        assert!(escape.position_at_phase(&body, 0.5).is_err());
+    }
+
+    #[test]
+    fn builds_earth_fluently() {
+        let earth = Body::builder()
+            .gravitational_parameter(3.986_004_418e14)
+            .radius(6.378_137e6)
+            .rotation_period(86_164.1)
+            .flattening(1.0 / 298.257_223_563)
+            .j2(1.082_63e-3)
+            .build()
+            .unwrap();
+        assert!((earth.k() - 3.986_004_418e14).abs() < 1.0);
+        assert!((earth.rotation_rate() - 7.292_1e-5).abs() < 1e-9);
+        assert_eq!((earth.j2, earth.j3, earth.j4), (Some(1.082_63e-3), None, None));
+
+        let sphere = Body::builder().mass(7.342e22).radius(1.737_4e6).build().unwrap();
+        assert_eq!((sphere.rotation_period, sphere.flattening, sphere.j2), (None, 0.0, None));
+        assert_eq!(Body::builder().mass(1.0).build().err(), Some(OrbitError::MissingField("radius".to_string())));
+    }
+
+    #[test]
+    fn propagation_before_the_unix_epoch_is_floored_or_rejected() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.2 };
+        let start = Anomaly::from_true(&orbit, 0.3, 1_000);
+        let floored = orbit.next_anomaly(&body, &start, -2.0);
+        assert_eq!(floored.time_ms, 0);
+        assert!(orbit.try_next_anomaly::<NewtonSolver>(&body, &start, -2.0, &SolverConfig::default()).is_err());
+        let at_epoch = orbit.try_next_anomaly::<NewtonSolver>(&body, &start, -1.0, &SolverConfig::default()).ok().unwrap();
+        assert_eq!(at_epoch.time_ms, 0);
+    }
 }
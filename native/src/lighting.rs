@@ -0,0 +1,64 @@
+//! Sun geometry relative to the orbit, for power and thermal analysis
+use std::f64::consts::{ PI };
+use crate::{ Body, Orbit, Vector3 };
+
+impl Orbit {
+
+    /// Radians in `[-π/2, π/2]`, the elevation of the Sun above the orbital plane, positive on the
+    /// side the angular momentum points to. `sun_direction` need not be a unit vector.
+    pub fn beta_angle(&self, sun_direction : Vector3) -> f64 {
+        self.normal().dot(sun_direction.unit()).clamp(-1.0, 1.0).asin()
+    }
+
+    /// Fraction of each revolution spent in the body's cylindrical shadow (see `forces::in_shadow`) at
+    /// beta angle `beta` (radians). Assumes a circular orbit, at the semi-major axis for an
+    /// elliptical one. Zero above the critical beta angle `asin(R / r)`, where the orbit clears the
+    /// shadow entirely, and for open orbits.
+    pub fn eclipse_fraction(&self, body : &Body, beta : f64) -> f64 {
+        let radius = match (self.orbital_period(body), self.semimajor_axis()) {
+            (Some(_), Some(a)) => a,
+            _ => return 0.0
+        };
+        if beta.abs() >= (body.radius / radius).min(1.0).asin() {
+            return 0.0;
+        }
+        let altitude = radius - body.radius;
+        let half_angle = ((altitude * altitude + 2.0 * body.radius * altitude).sqrt() / (radius * beta.cos())).clamp(-1.0, 1.0).acos();
+        half_angle / PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ earth, Orbit, Vector3 };
+    use crate::forces::{ in_shadow };
+
+    #[test]
+    fn beta_angle_is_elevation_above_the_plane() {
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.3, semimajor_axis : 6.771e6 };
+        let normal = Vector3::k_hat().rotate_x(0.3);
+        assert!((orbit.beta_angle(normal * 1.5e11) - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+        assert!((orbit.beta_angle(-normal) + std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+        assert!(orbit.beta_angle(Vector3::i_hat()).abs() < 1e-12);
+        assert!((orbit.beta_angle(Vector3::new(0.0, 0.0, 1.0)) - (std::f64::consts::FRAC_PI_2 - 0.3)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn eclipse_fraction_matches_shadow_sampling() {
+        let body = earth();
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 6.771e6 };
+        for &beta in [0.0f64, 0.5, 1.0].iter() {
+            let sun = Vector3::new(beta.cos(), 0.0, beta.sin()) * 1.5e11;
+            assert!((orbit.beta_angle(sun) - beta).abs() < 1e-12);
+            let samples = 100_000;
+            let shadowed = (0..samples)
+                .filter(|&k| {
+                    let angle = 2.0 * std::f64::consts::PI * k as f64 / samples as f64;
+                    in_shadow(Vector3::new(angle.cos(), angle.sin(), 0.0) * 6.771e6, sun, body.radius)
+                })
+                .count();
+            assert!((orbit.eclipse_fraction(&body, beta) - shadowed as f64 / samples as f64).abs() < 1e-4);
+        }
+        assert_eq!(orbit.eclipse_fraction(&body, 1.5), 0.0);
+    }
+}
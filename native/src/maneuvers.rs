@@ -0,0 +1,165 @@
+//! Impulsive maneuvers. Delta-v values are meters/second, positive along the direction of motion.
+use std::f64::consts::{ PI };
+use crate::{ Anomaly, Body, Orbit, OrbitError };
+
+impl Orbit {
+
+    /// Signed `target - self` differences in [semi-major axis (m), eccentricity, inclination,
+    /// ascending node, angle of periapsis], the angles (radians) wrapped to `[-π, π)` so that 350° to
+    /// 10° reads as +20°. The semi-major axis difference is NaN if either orbit is parabolic.
+    pub fn element_difference(&self, target : &Orbit) -> [f64; 5] {
+        let semimajor_axis = |orbit : &Orbit| orbit.semimajor_axis().unwrap_or(f64::NAN);
+        [
+            semimajor_axis(target) - semimajor_axis(self),
+            target.eccentricity() - self.eccentricity(),
+            wrap_angle(target.inclination() - self.inclination()),
+            wrap_angle(target.ascending_node() - self.ascending_node()),
+            wrap_angle(target.angle_of_periapsis() - self.angle_of_periapsis())
+        ]
+    }
+
+    /// Radians in `[0, π]` between the two orbital planes, the angle between their angular momenta
+    /// and so the plane change needed to match `other`. Unlike the difference of the inclinations
+    /// this accounts for the nodes: equally inclined planes with different nodes are still apart.
+    pub fn relative_inclination(&self, other : &Orbit) -> f64 {
+        let (normal, other_normal) = (self.normal(), other.normal());
+        // atan2 rather than acos of the dot product keeps precision for nearly coplanar orbits
+        normal.cross(other_normal).magnitude().atan2(normal.dot(other_normal))
+    }
+
+    /// Phasing orbit departing from and returning to this orbit's periapsis, which after `revolutions`
+    /// laps lets the chaser meet a target `phase_angle` radians ahead of it on this same orbit, along
+    /// with the (departure, return) burns. A positive phase angle catches up to a target ahead on a
+    /// lower, faster orbit; a negative one falls back to a target behind on a higher, slower one.
+    /// None for open orbits, zero revolutions, or a phase too large to make up in that many laps: one
+    /// whose phasing orbit would dip beneath the body's surface on the far side.
+    pub fn phasing_orbit(&self, body : &Body, phase_angle : f64, revolutions : u32) -> Option<(Orbit, f64, f64)> {
+        let period = self.orbital_period(body)?;
+        if revolutions == 0 {
+            return None;
+        }
+        let phasing_period = period * (1.0 - phase_angle / (2.0 * PI * revolutions as f64));
+        if phasing_period <= 0.0 {
+            return None;
+        }
+
+        let burn_radius = self.periapsis();
+        let semimajor_axis = (body.k() * (phasing_period / (2.0 * PI)).powi(2)).cbrt();
+        let opposite_radius = 2.0 * semimajor_axis - burn_radius;
+        if opposite_radius <= 0.0 || opposite_radius < body.radius {
+            return None;
+        }
+        let (periapsis, angle_of_periapsis) = if opposite_radius >= burn_radius {
+            (burn_radius, self.angle_of_periapsis())
+        } else {
+            (opposite_radius, (self.angle_of_periapsis() + PI).rem_euclid(2.0 * PI))
+        };
+        let eccentricity = (opposite_radius - burn_radius).abs() / (opposite_radius + burn_radius);
+        let phasing = Orbit::from_elements(periapsis, eccentricity, self.inclination(), self.ascending_node(), angle_of_periapsis);
+
+        let burn = phasing.vis_viva(body, burn_radius) - self.vis_viva(body, burn_radius);
+        Some((phasing, burn, -burn))
+    }
+
+    /// Speed to add along the direction of motion at `anomaly` to leave on a parabola, the escape
+    /// velocity there less the current speed. Smallest at periapsis, where the orbit is fastest
+    /// relative to escape; zero on a parabolic orbit and negative on a hyperbolic one.
+    pub fn delta_v_to_escape(&self, body : &Body, anomaly : &Anomaly) -> f64 {
+        let radius = self.distance_from_parent(anomaly);
+        body.escape_velocity(radius) - self.vis_viva(body, radius)
+    }
+
+    /// Meters/second of a single prograde burn at apoapsis raising periapsis to `target_periapsis`
+    /// meters. A target beyond apoapsis is allowed, the burn point becoming the new periapsis.
+    /// `OrbitError::Unreachable` for a target below the current periapsis, which needs a retrograde
+    /// burn, and for open orbits, which have no apoapsis to burn at.
+    pub fn delta_v_to_raise_periapsis(&self, body : &Body, target_periapsis : f64) -> Result<f64, OrbitError> {
+        let apoapsis = self.apoapsis()
+            .ok_or_else(|| OrbitError::Unreachable("open orbits have no apoapsis".to_string()))?;
+        if target_periapsis < self.periapsis() {
+            return Err(OrbitError::Unreachable(format!("target periapsis {} m is below the current {} m", target_periapsis, self.periapsis())));
+        }
+        let raised_inverse_a = 2.0 / (apoapsis + target_periapsis);
+        let raised_speed = (body.k() * (2.0 / apoapsis - raised_inverse_a)).sqrt();
+        Ok(raised_speed - self.vis_viva(body, apoapsis))
+    }
+}
+
+/// Radians into `[-π, π)`
+pub(crate) fn wrap_angle(angle : f64) -> f64 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ earth, Anomaly, Orbit };
+
+    #[test]
+    fn phasing_period_makes_up_the_phase() {
+        let body = earth();
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.4, semimajor_axis : 7.0e6 };
+        let period = orbit.orbital_period(&body).unwrap();
+        let n = orbit.mean_motion(&body);
+        for &phase in [0.5, -0.5].iter() {
+            let (phasing, departure, arrival) = orbit.phasing_orbit(&body, phase, 2).unwrap();
+            let phasing_period = phasing.orbital_period(&body).unwrap();
+            assert!((2.0 * phasing_period - (2.0 * period - phase / n)).abs() < 1e-6);
+            assert!((departure + arrival).abs() < 1e-12);
+            assert_eq!(departure < 0.0, phase > 0.0);
+        }
+        assert!(orbit.phasing_orbit(&body, 0.5, 0).is_none());
+        // Far enough ahead that the phasing orbit's far side goes through the center
+        assert!(orbit.phasing_orbit(&body, 0.7 * 2.0 * std::f64::consts::PI, 1).is_none());
+        // Or only through the surface
+        assert!(orbit.phasing_orbit(&body, 0.3 * 2.0 * std::f64::consts::PI, 1).is_none());
+    }
+
+    #[test]
+    fn element_difference_wraps_angles() {
+        let from = Orbit::Elliptical { angle_of_periapsis : 350f64.to_radians(), ascending_node : 10f64.to_radians(), inclination : 0.5, periapsis : 7.0e6, eccentricity : 0.1 };
+        let to = Orbit::Elliptical { angle_of_periapsis : 10f64.to_radians(), ascending_node : 350f64.to_radians(), inclination : 0.6, periapsis : 7.7e6, eccentricity : 0.2 };
+        let difference = from.element_difference(&to);
+        let expected = [7.7e6 / 0.8 - 7.0e6 / 0.9, 0.1, 0.1, -20f64.to_radians(), 20f64.to_radians()];
+        for (actual, expected) in difference.iter().zip(expected.iter()) {
+            assert!((actual - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn escape_is_cheapest_at_periapsis() {
+        let body = earth();
+        let circular = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        let speed = circular.vis_viva(&body, 7.0e6);
+        assert!((circular.delta_v_to_escape(&body, &Anomaly::from_true(&circular, 1.0, 0)) - (2f64.sqrt() - 1.0) * speed).abs() < 1e-9);
+        let elliptical = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : 0.3 };
+        let at = |nu : f64| elliptical.delta_v_to_escape(&body, &Anomaly::from_true(&elliptical, nu, 0));
+        assert!(at(0.0) < at(0.5) && at(0.5) < at(std::f64::consts::PI));
+        let parabolic = Orbit::Parabolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6 };
+        assert!(parabolic.delta_v_to_escape(&body, &Anomaly::from_true(&parabolic, 1.0, 0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn raising_periapsis_to_apoapsis_circularizes() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 6.5e6, eccentricity : 0.1 };
+        let apoapsis = orbit.apoapsis().unwrap();
+        let circular = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : apoapsis };
+        let expected = circular.vis_viva(&body, apoapsis) - orbit.vis_viva(&body, apoapsis);
+        assert!((orbit.delta_v_to_raise_periapsis(&body, apoapsis).unwrap() - expected).abs() < 1e-9);
+        assert_eq!(orbit.delta_v_to_raise_periapsis(&body, orbit.periapsis()), Ok(0.0));
+        assert!(orbit.delta_v_to_raise_periapsis(&body, 6.4e6).is_err());
+    }
+
+    #[test]
+    fn relative_inclination_sees_the_nodes() {
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.5, semimajor_axis : 7.0e6 };
+        let turned = Orbit::Elliptical { angle_of_periapsis : 1.0, ascending_node : std::f64::consts::FRAC_PI_2, inclination : 0.5, periapsis : 7.0e6, eccentricity : 0.1 };
+        // Spherical law of cosines for the angle between the planes
+        let expected = (0.5f64.cos().powi(2) + 0.5f64.sin().powi(2) * std::f64::consts::FRAC_PI_2.cos()).acos();
+        assert!((orbit.relative_inclination(&turned) - expected).abs() < 1e-12);
+        assert!(orbit.relative_inclination(&turned) > 0.5);
+        let tilted = Orbit::Circular { angle_of_periapsis : 2.0, ascending_node : 0.0, inclination : 0.8, semimajor_axis : 8.0e6 };
+        assert!((orbit.relative_inclination(&tilted) - 0.3).abs() < 1e-12);
+        assert!(orbit.relative_inclination(&orbit).abs() < 1e-15);
+    }
+}
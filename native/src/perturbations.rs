@@ -0,0 +1,151 @@
+//! Secular effects of the body's oblateness (J2) on an orbit
+use std::f64::consts::{ PI };
+use crate::{ offset_time_ms, Anomaly, Body, Orbit };
+
+impl Orbit {
+
+    /// (R/p)^2 · n, the factor every first-order J2 secular rate shares
+    fn j2_rate_scale(&self, body : &Body) -> f64 {
+        (body.radius / self.parameter()).powi(2) * self.mean_motion(body)
+    }
+
+    /// Radians/second drift of the ascending node under J2, negative (westward) for prograde orbits
+    pub fn nodal_precession_rate(&self, body : &Body, j2 : f64) -> f64 {
+        -1.5 * j2 * self.j2_rate_scale(body) * self.inclination().cos()
+    }
+
+    /// Radians/second drift of the argument of periapsis under J2, reversing at the critical inclination
+    pub fn apsidal_precession_rate(&self, body : &Body, j2 : f64) -> f64 {
+        let sin_i = self.inclination().sin();
+        0.75 * j2 * self.j2_rate_scale(body) * (4.0 - 5.0 * sin_i * sin_i)
+    }
+
+    /// Radians/second advance of the mean anomaly, the Keplerian mean motion plus its J2 correction
+    pub fn mean_anomaly_rate(&self, body : &Body, j2 : f64) -> f64 {
+        let e = self.eccentricity();
+        let sin_i = self.inclination().sin();
+        self.mean_motion(body) + 0.75 * j2 * self.j2_rate_scale(body) * (1.0 - e * e).sqrt() * (2.0 - 3.0 * sin_i * sin_i)
+    }
+
+    /// (node, argument of periapsis, mean anomaly) secular rates in radians/second under J2, the last
+    /// being only the correction to the Keplerian mean motion, so each mean element advances
+    /// linearly by its rate times the elapsed time
+    pub fn j2_secular_rates(&self, body : &Body, j2 : f64) -> (f64, f64, f64) {
+        (
+            self.nodal_precession_rate(body, j2),
+            self.apsidal_precession_rate(body, j2),
+            self.mean_anomaly_rate(body, j2) - self.mean_motion(body)
+        )
+    }
+
+    /// This orbit and `start` advanced `dt` seconds by the secular J2 rates alone: the node, argument
+    /// of periapsis, and mean anomaly move linearly while the shape and inclination stay fixed.
+    /// Short-period oscillations are left out, so positions are mean rather than osculating, but the
+    /// drift stays right over spans of many days where ground tracks and coverage are concerned. As in
+    /// `next_anomaly`, the true anomaly of `start` is authoritative and its mean anomaly rederived.
+    pub fn propagate_j2_secular(&self, body : &Body, j2 : f64, start : &Anomaly, dt : f64) -> (Orbit, Anomaly) {
+        let (node_rate, periapsis_rate, _) = self.j2_secular_rates(body, j2);
+        let orbit = self.with_orientation(
+            self.inclination(),
+            (self.ascending_node() + node_rate * dt).rem_euclid(2.0 * PI),
+            (self.angle_of_periapsis() + periapsis_rate * dt).rem_euclid(2.0 * PI)
+        );
+        let (_, start_mean) = self.anomalies_from_true(start.true_anomaly);
+        let mut mean_anomaly = start_mean + self.mean_anomaly_rate(body, j2) * dt;
+        if self.orbital_period(body).is_some() {
+            mean_anomaly = mean_anomaly.rem_euclid(2.0 * PI);
+        }
+        let anomaly = Anomaly::from_mean(&orbit, mean_anomaly, offset_time_ms(start.time_ms, dt));
+        (orbit, anomaly)
+    }
+
+    /// Seconds between successive periapsis passages (the anomalistic period), None for open orbits.
+    /// Under J2 the mean anomaly advances faster or slower than the Keplerian mean motion, so this
+    /// differs from `orbital_period`, which assumes a point-mass body.
+    pub fn anomalistic_period(&self, body : &Body, j2 : f64) -> Option<f64> {
+        self.orbital_period(body).map(|_| 2.0 * PI / self.mean_anomaly_rate(body, j2))
+    }
+
+    /// Seconds between successive ascending node crossings (the draconic period), None for open orbits.
+    /// Under J2 the periapsis drifts relative to the node, so this is `2π / (Ṁ + ω̇)` rather than the
+    /// Keplerian `orbital_period`. It is the period that sets ground-track repeat cycles.
+    pub fn nodal_period(&self, body : &Body, j2 : f64) -> Option<f64> {
+        self.orbital_period(body)
+            .map(|_| 2.0 * PI / (self.mean_anomaly_rate(body, j2) + self.apsidal_precession_rate(body, j2)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ Anomaly, Body, Orbit };
+
+    const EARTH_J2 : f64 = 1.082_63e-3;
+
+    #[test]
+    fn nodal_period_differs_under_j2() {
+        let body = Body::builder().mass(5.972e24).radius(6.378e6).build().unwrap();
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.9, semimajor_axis : 6.978e6 };
+        let keplerian = orbit.orbital_period(&body).unwrap();
+        let nodal = orbit.nodal_period(&body, EARTH_J2).unwrap();
+        assert_eq!(orbit.nodal_period(&body, 0.0), Some(keplerian));
+        // A LEO draconic period is a few seconds off the Keplerian one
+        assert!((nodal - keplerian).abs() > 1.0);
+        assert!((nodal - keplerian).abs() < 60.0);
+    }
+
+    #[test]
+    fn periods_separate_under_j2() {
+        let body = Body::builder().mass(5.972e24).radius(6.378e6).build().unwrap();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.5, periapsis : 6.878e6, eccentricity : 0.01 };
+        let keplerian = orbit.orbital_period(&body).unwrap();
+        let anomalistic = orbit.anomalistic_period(&body, EARTH_J2).unwrap();
+        let nodal = orbit.nodal_period(&body, EARTH_J2).unwrap();
+        assert!((keplerian - anomalistic).abs() > 1.0);
+        assert!((keplerian - nodal).abs() > 1.0);
+        assert!((anomalistic - nodal).abs() > 1.0);
+
+        let escape = Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.5, periapsis : 6.878e6, eccentricity : 1.2 };
+        assert!(escape.anomalistic_period(&body, EARTH_J2).is_none());
+    }
+
+    #[test]
+    fn secular_rates_match_published_leo_values() {
+        let body = Body::builder().gravitational_parameter(3.986_004_418e14).radius(6.378_137e6).build().unwrap();
+        let degrees_per_day = 86_400.0f64.to_degrees();
+        // Sun-synchronous at 700 km: the node turns with the mean Sun, 360° per tropical year
+        let sun_synchronous = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 98.19f64.to_radians(), semimajor_axis : 7.078_137e6 };
+        let (node, _, _) = sun_synchronous.j2_secular_rates(&body, EARTH_J2);
+        assert!((node * degrees_per_day - 0.9856).abs() < 0.005, "{}", node * degrees_per_day);
+
+        // ISS-like at 400 km, 51.6°: node about -5.0°/day, perigee about +3.7°/day
+        let station = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 51.6f64.to_radians(), semimajor_axis : 6.778_137e6 };
+        let (node, periapsis, mean) = station.j2_secular_rates(&body, EARTH_J2);
+        assert!((node * degrees_per_day + 5.0).abs() < 0.1, "{}", node * degrees_per_day);
+        assert!((periapsis * degrees_per_day - 3.7).abs() < 0.1, "{}", periapsis * degrees_per_day);
+        assert!((mean - (station.mean_anomaly_rate(&body, EARTH_J2) - station.mean_motion(&body))).abs() < 1e-18);
+    }
+
+    #[test]
+    fn secular_propagation_drifts_the_node() {
+        let body = Body::builder().mass(5.972e24).radius(6.378e6).build().unwrap();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.4, ascending_node : 1.0, inclination : 0.9, periapsis : 6.878e6, eccentricity : 0.01 };
+        let start = Anomaly::from_mean(&orbit, 0.2, 1_000);
+        let day = 86_400.0;
+        let (moved, anomaly) = orbit.propagate_j2_secular(&body, EARTH_J2, &start, day);
+        let (node_rate, periapsis_rate, _) = orbit.j2_secular_rates(&body, EARTH_J2);
+        assert!((moved.ascending_node() - (1.0 + node_rate * day)).abs() < 1e-12);
+        assert!((moved.angle_of_periapsis() - (0.4 + periapsis_rate * day)).abs() < 1e-12);
+        assert_eq!((moved.periapsis(), moved.eccentricity(), moved.inclination()), (6.878e6, 0.01, 0.9));
+        let expected = (0.2 + orbit.mean_anomaly_rate(&body, EARTH_J2) * day).rem_euclid(2.0 * std::f64::consts::PI);
+        assert!((anomaly.mean_anomaly - expected).abs() < 1e-9);
+        assert_eq!(anomaly.time_ms, 1_000 + 86_400_000);
+
+        let (same, unmoved) = orbit.propagate_j2_secular(&body, 0.0, &start, day);
+        assert_eq!(same.ascending_node(), orbit.ascending_node());
+        assert!((unmoved.mean_anomaly - orbit.next_anomaly(&body, &start, day).mean_anomaly).abs() < 1e-9);
+
+        let stale = Anomaly { mean_anomaly : 3.0, ..start };
+        let (_, restarted) = orbit.propagate_j2_secular(&body, EARTH_J2, &stale, day);
+        assert!((restarted.mean_anomaly - anomaly.mean_anomaly).abs() < 1e-12);
+    }
+}
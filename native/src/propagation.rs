@@ -0,0 +1,274 @@
+//! Interchangeable strategies for moving an object along its orbit
+use crate::{ offset_time_ms, try_offset_time_ms, Anomaly, Body, Orbit, OrbitError, StateVector, Vector3 };
+
+/// Advances an object `dt` seconds (negative for backward) from `state` on `orbit`, returning its
+/// anomaly on that same orbit with the time moved to match. Generic code can take any
+/// `P : Propagator`; `KeplerPropagator` is the one to reach for by default.
+pub trait Propagator {
+    fn propagate(&self, orbit : &Orbit, body : &Body, state : &Anomaly, dt : f64) -> Anomaly;
+}
+
+/// Two-body motion solved analytically, `Orbit::next_anomaly`
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct KeplerPropagator;
+
+impl Propagator for KeplerPropagator {
+    fn propagate(&self, orbit : &Orbit, body : &Body, state : &Anomaly, dt : f64) -> Anomaly {
+        orbit.next_anomaly(body, state, dt)
+    }
+}
+
+/// Two-body motion integrated numerically by fourth-order Runge-Kutta, in equal steps no longer
+/// than `step` seconds, and the end state located on the orbit by its direction in the orbital
+/// plane. Serves as a check on the analytic solution and the starting point for adding forces;
+/// the error over a step grows as its fifth power. `propagate` panics unless `step` is positive.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct Rk4Propagator {
+    /// seconds
+    pub step : f64
+}
+
+impl Propagator for Rk4Propagator {
+    fn propagate(&self, orbit : &Orbit, body : &Body, state : &Anomaly, dt : f64) -> Anomaly {
+        assert!(self.step > 0.0, "Rk4Propagator step must be positive, got {}", self.step);
+        let k = body.k();
+        let start = orbit.state_vector(body, state);
+        let acceleration = |position : Vector3| position * (-k / position.magnitude().powi(3));
+        let steps = (dt.abs() / self.step).ceil().max(1.0) as usize;
+        let h = dt / steps as f64;
+        let (mut position, mut velocity) = (start.position(), start.velocity());
+        for _ in 0..steps {
+            let (next_position, next_velocity) = rk4_step(position, velocity, h, acceleration);
+            position = next_position;
+            velocity = next_velocity;
+        }
+        anomaly_along(orbit, &StateVector::new(position, velocity, offset_time_ms(state.time_ms, dt)))
+    }
+}
+
+/// Attempted steps, accepted or not, after which `integrate_adaptive` stops short of `t_end`
+const MAX_ADAPTIVE_STEPS : usize = 1_000_000;
+
+/// Two-body motion from `start`, brought over to ECI as in `StateVector::inertial`, integrated
+/// `t_end` seconds (negative for backward) by the Runge-Kutta-Fehlberg 4(5) pair, each step sized
+/// so the difference between the two orders, relative to the size of the position and velocity,
+/// stays under `tol`. The fifth order solution is the one carried forward. Steps shrink at
+/// periapsis and stretch at apoapsis on their own, so high eccentricities cost little more than
+/// circles.
+///
+/// Returns the ECI state after every accepted step, `start` first and the state at `t_end` last,
+/// timed from `start.time_ms`, along with the number of steps attempted including rejected ones.
+/// Gives up after a million attempts, ending wherever it got to. Fails with
+/// `OrbitError::InvalidValue` for a `tol` that isn't positive or a body-fixed `start`, and
+/// `OrbitError::Unreachable` for an end before the Unix epoch.
+pub fn integrate_adaptive(start : &StateVector, body : &Body, t_end : f64, tol : f64) -> Result<(Vec<StateVector>, usize), OrbitError> {
+    if tol.is_nan() || tol <= 0.0 {
+        return Err(OrbitError::InvalidValue { keyword : "tol".to_string(), value : tol.to_string() });
+    }
+    try_offset_time_ms(start.time_ms(), t_end)?;
+    let start = start.inertial()?;
+    let (position, velocity) = (start.position(), start.velocity());
+    let k = body.k();
+    let acceleration = |position : Vector3| position * (-k / position.magnitude().powi(3));
+    let derivative = |(r, v) : (Vector3, Vector3)| (v, acceleration(r));
+    let combine = |(r, v) : (Vector3, Vector3), terms : &[((Vector3, Vector3), f64)], h : f64| {
+        terms.iter().fold((r, v), |(r, v), &((dr, dv), weight)| (r + dr * (weight * h), v + dv * (weight * h)))
+    };
+
+    let mut states = vec![StateVector::new(position, velocity, start.time_ms())];
+    let (mut y, mut t) = ((position, velocity), 0.0);
+    let mut h = (0.01 * position.magnitude() / velocity.magnitude()).min(t_end.abs()).copysign(t_end);
+    let mut attempts = 0;
+    while (t_end - t).abs() > 1e-9 * t_end.abs() && attempts < MAX_ADAPTIVE_STEPS {
+        attempts += 1;
+        if (t + h - t_end) * h.signum() > 0.0 {
+            h = t_end - t;
+        }
+        let k1 = derivative(y);
+        let k2 = derivative(combine(y, &[(k1, 1.0 / 4.0)], h));
+        let k3 = derivative(combine(y, &[(k1, 3.0 / 32.0), (k2, 9.0 / 32.0)], h));
+        let k4 = derivative(combine(y, &[(k1, 1932.0 / 2197.0), (k2, -7200.0 / 2197.0), (k3, 7296.0 / 2197.0)], h));
+        let k5 = derivative(combine(y, &[(k1, 439.0 / 216.0), (k2, -8.0), (k3, 3680.0 / 513.0), (k4, -845.0 / 4104.0)], h));
+        let k6 = derivative(combine(y, &[(k1, -8.0 / 27.0), (k2, 2.0), (k3, -3544.0 / 2565.0), (k4, 1859.0 / 4104.0), (k5, -11.0 / 40.0)], h));
+        let fourth = combine(y, &[(k1, 25.0 / 216.0), (k3, 1408.0 / 2565.0), (k4, 2197.0 / 4104.0), (k5, -1.0 / 5.0)], h);
+        let fifth = combine(y, &[(k1, 16.0 / 135.0), (k3, 6656.0 / 12825.0), (k4, 28561.0 / 56430.0), (k5, -9.0 / 50.0), (k6, 2.0 / 55.0)], h);
+
+        let error = ((fifth.0 - fourth.0).magnitude() / fifth.0.magnitude()).max((fifth.1 - fourth.1).magnitude() / fifth.1.magnitude());
+        if error <= tol {
+            t += h;
+            y = fifth;
+            states.push(StateVector::new(y.0, y.1, offset_time_ms(start.time_ms(), t)));
+        }
+        // The usual safety factor, with the change per step bounded either way
+        let factor = if error > 0.0 { 0.9 * (tol / error).powf(0.2) } else { 5.0 };
+        h *= factor.clamp(0.2, 5.0);
+    }
+    Ok((states, attempts))
+}
+
+/// Two-body motion from `start`, brought over to ECI as in `StateVector::inertial`, integrated
+/// `steps` steps of `dt` seconds (negative for backward) by leapfrog (kick-drift-kick). Second order only, but symplectic: the energy
+/// error oscillates with each orbit instead of accumulating, so it stays bounded over spans where
+/// Runge-Kutta drifts off, at one acceleration per step. Returns `start` and the state after every
+/// step in ECI, timed from `start.time_ms`. Fails with `OrbitError::InvalidValue` for a body-fixed
+/// `start` and `OrbitError::Unreachable` for an end before the Unix epoch.
+pub fn integrate_symplectic(start : &StateVector, body : &Body, dt : f64, steps : usize) -> Result<Vec<StateVector>, OrbitError> {
+    try_offset_time_ms(start.time_ms(), dt * steps as f64)?;
+    let start = start.inertial()?;
+    let k = body.k();
+    let acceleration = |position : Vector3| position * (-k / position.magnitude().powi(3));
+    let mut states = Vec::with_capacity(steps + 1);
+    let (mut position, mut velocity) = (start.position(), start.velocity());
+    states.push(StateVector::new(position, velocity, start.time_ms()));
+    let mut pull = acceleration(position);
+    for step in 1..=steps {
+        let half = velocity + pull * (dt / 2.0);
+        position = position + half * dt;
+        pull = acceleration(position);
+        velocity = half + pull * (dt / 2.0);
+        states.push(StateVector::new(position, velocity, offset_time_ms(start.time_ms(), dt * step as f64)));
+    }
+    Ok(states)
+}
+
+/// One fourth-order Runge-Kutta step of `h` seconds under `acceleration`, as (position, velocity)
+fn rk4_step(position : Vector3, velocity : Vector3, h : f64, acceleration : impl Fn(Vector3) -> Vector3) -> (Vector3, Vector3) {
+    let (r1, v1) = (velocity, acceleration(position));
+    let (r2, v2) = (velocity + v1 * (h / 2.0), acceleration(position + r1 * (h / 2.0)));
+    let (r3, v3) = (velocity + v2 * (h / 2.0), acceleration(position + r2 * (h / 2.0)));
+    let (r4, v4) = (velocity + v3 * h, acceleration(position + r3 * h));
+    (position + (r1 + r2 * 2.0 + r3 * 2.0 + r4) * (h / 6.0), velocity + (v1 + v2 * 2.0 + v3 * 2.0 + v4) * (h / 6.0))
+}
+
+/// The anomaly on `orbit` in the direction of `state`'s position, at its time
+pub(crate) fn anomaly_along(orbit : &Orbit, state : &StateVector) -> Anomaly {
+    let position = state.position();
+    let periapsis = orbit.perifocal_to_inertial(Vector3::i_hat());
+    let along_motion = orbit.perifocal_to_inertial(Vector3::j_hat());
+    Anomaly::from_true(orbit, position.dot(along_motion).atan2(position.dot(periapsis)), state.time_ms())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ earth };
+    use crate::maneuvers::{ wrap_angle };
+
+    /// Generic over the strategy, as downstream code would be
+    fn true_anomaly_after<P : Propagator>(propagator : &P, orbit : &Orbit, body : &Body, dt : f64) -> f64 {
+        propagator.propagate(orbit, body, &Anomaly::from_true(orbit, 0.3, 1_000_000), dt).true_anomaly
+    }
+
+    #[test]
+    fn rk4_agrees_with_kepler() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.4, ascending_node : 1.0, inclination : 0.6, periapsis : 7.0e6, eccentricity : 0.3 };
+        for &dt in [2000.0, -900.0].iter() {
+            let analytic = true_anomaly_after(&KeplerPropagator, &orbit, &body, dt);
+            let numeric = true_anomaly_after(&Rk4Propagator { step : 10.0 }, &orbit, &body, dt);
+            assert!(wrap_angle(analytic - numeric).abs() < 1e-9, "{} vs {}", analytic, numeric);
+        }
+        let moved = Rk4Propagator { step : 10.0 }.propagate(&orbit, &body, &Anomaly::from_true(&orbit, 0.3, 1_000_000), -900.0);
+        assert_eq!(moved.time_ms, 100_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "step must be positive")]
+    fn rk4_rejects_a_zero_step() {
+        let body = earth();
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 7.0e6 };
+        true_anomaly_after(&Rk4Propagator { step : 0.0 }, &orbit, &body, 60.0);
+    }
+
+    #[test]
+    fn adaptive_steps_follow_the_eccentric_orbit() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.4, ascending_node : 1.0, inclination : 0.6, periapsis : 7.0e6, eccentricity : 0.9 };
+        let start = orbit.state_vector(&body, &Anomaly::from_true(&orbit, 0.0, 1_600_000_000_000));
+        let period = orbit.orbital_period(&body).unwrap();
+        let (states, attempts) = integrate_adaptive(&start, &body, period, 1e-12).ok().unwrap();
+        let end = states.last().unwrap();
+        assert_eq!(end.time_ms(), offset_time_ms(start.time_ms(), period));
+        assert!((end.position() - start.position()).magnitude() < 10.0, "{}", (end.position() - start.position()).magnitude());
+        assert!(attempts >= states.len() - 1);
+
+        // Short steps through periapsis, long ones out at apoapsis
+        let steps : Vec<f64> = states.windows(2).map(|pair| (pair[1].time_ms() - pair[0].time_ms()) as f64 / 1000.0).collect();
+        let shortest = steps.iter().cloned().fold(f64::INFINITY, f64::min);
+        let longest = steps.iter().cloned().fold(0.0, f64::max);
+        assert!(longest > 20.0 * shortest, "{} vs {}", longest, shortest);
+        // Far fewer than a fixed step short enough for periapsis would need over the period
+        assert!((attempts as f64) < period / shortest / 5.0, "{} vs {}", attempts, period / shortest);
+    }
+
+    #[test]
+    fn adaptive_runs_backward_on_the_start_clock() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.4, ascending_node : 1.0, inclination : 0.6, periapsis : 7.0e6, eccentricity : 0.3 };
+        let start = orbit.state_vector(&body, &Anomaly::from_true(&orbit, 1.0, 1_600_000_000_000));
+        let (states, _) = integrate_adaptive(&start, &body, -1_000.0, 1e-12).ok().unwrap();
+        assert!(states.windows(2).all(|pair| pair[1].time_ms() < pair[0].time_ms()));
+        let end = states.last().unwrap();
+        assert_eq!(end.time_ms(), start.time_ms() - 1_000_000);
+        let expected = orbit.state_vector(&body, &orbit.next_anomaly(&body, &Anomaly::from_true(&orbit, 1.0, 0), -1_000.0)).position();
+        assert!((end.position() - expected).magnitude() < 1e-2, "{}", (end.position() - expected).magnitude());
+
+        assert!(matches!(integrate_adaptive(&start, &body, -1.7e9, 1e-10), Err(OrbitError::Unreachable(_))));
+        assert!(matches!(integrate_adaptive(&start, &body, 1_000.0, 0.0), Err(OrbitError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn symplectic_energy_stays_bounded() {
+        let body = earth();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.3, periapsis : 7.0e6, eccentricity : 0.2 };
+        let start = orbit.state_vector(&body, &Anomaly::from_true(&orbit, 0.0, 1_600_000_000_000));
+        let energy = |position : Vector3, velocity : Vector3| velocity.magnitude().powi(2) / 2.0 - body.k() / position.magnitude();
+        let initial = energy(start.position(), start.velocity());
+        let per_orbit = 100;
+        let orbits = 500;
+        let dt = orbit.orbital_period(&body).unwrap() / per_orbit as f64;
+
+        let states = integrate_symplectic(&start, &body, dt, per_orbit * orbits).ok().unwrap();
+        assert_eq!(states.len(), per_orbit * orbits + 1);
+        assert_eq!(states[per_orbit].time_ms(), offset_time_ms(start.time_ms(), dt * per_orbit as f64));
+        let worst = |chunk : &[StateVector]| chunk.iter()
+            .map(|state| ((energy(state.position(), state.velocity()) - initial) / initial).abs())
+            .fold(0.0, f64::max);
+        let first = worst(&states[..per_orbit]);
+        let last = worst(&states[states.len() - per_orbit..]);
+        assert!(last < 1.1 * first, "{} vs {}", last, first);
+
+        // RK4 at the same step starts out far more accurate but keeps losing energy
+        let acceleration = |position : Vector3| position * (-body.k() / position.magnitude().powi(3));
+        let (mut position, mut velocity) = (start.position(), start.velocity());
+        let mut drift = Vec::new();
+        for _ in 0..orbits {
+            for _ in 0..per_orbit {
+                let (next_position, next_velocity) = rk4_step(position, velocity, dt, acceleration);
+                position = next_position;
+                velocity = next_velocity;
+            }
+            drift.push(((energy(position, velocity) - initial) / initial).abs());
+        }
+        assert!(drift[0] < first);
+        assert!(drift[orbits - 1] > 100.0 * drift[0], "{} vs {}", drift[orbits - 1], drift[0]);
+    }
+
+    #[test]
+    fn symplectic_runs_backward_on_the_start_clock() {
+        let body = earth();
+        let orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.3, semimajor_axis : 7.0e6 };
+        let start = orbit.state_vector(&body, &Anomaly::from_true(&orbit, 0.0, 5_000_000));
+        let states = integrate_symplectic(&start, &body, -1.0, 1000).ok().unwrap();
+        assert_eq!(states.last().unwrap().time_ms(), 4_000_000);
+        let expected = orbit.state_vector(&body, &orbit.next_anomaly(&body, &Anomaly::from_true(&orbit, 0.0, 0), -1_000.0)).position();
+        // Leapfrog's phase error grows with the square of the step: 1.26 m here, 126 m at 10 s
+        assert!((states.last().unwrap().position() - expected).magnitude() < 2.0);
+        assert!(integrate_symplectic(&start, &body, -10.0, 501).is_err());
+    }
+}
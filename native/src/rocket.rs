@@ -0,0 +1,36 @@
+//! Propellant budgets for the delta-v the maneuver functions return
+
+/// m/s^2, standard gravity g0, relating specific impulse to exhaust velocity
+pub const STANDARD_GRAVITY : f64 = 9.806_65;
+
+/// Initial over final mass needed for `delta_v` (m/s) with exhaust velocity `exhaust_velocity` (m/s),
+/// the Tsiolkovsky rocket equation `exp(Δv / v_e)`
+pub fn mass_ratio(delta_v : f64, exhaust_velocity : f64) -> f64 {
+    (delta_v / exhaust_velocity).exp()
+}
+
+/// Meters/second of delta-v a stage with the given initial over final mass achieves, `v_e · ln(m0 / m1)`
+pub fn delta_v_from_mass_ratio(mass_ratio : f64, exhaust_velocity : f64) -> f64 {
+    exhaust_velocity * mass_ratio.ln()
+}
+
+/// Exhaust velocity in m/s equivalent to a specific impulse in seconds, `Isp · g0`, for passing
+/// to the functions above
+pub fn exhaust_velocity(specific_impulse : f64) -> f64 {
+    specific_impulse * STANDARD_GRAVITY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rocket_equation_round_trips() {
+        let v_e = exhaust_velocity(311.0);
+        assert!((v_e - 3049.8).abs() < 0.1);
+        let ratio = mass_ratio(3900.0, v_e);
+        assert!((ratio - 3.59).abs() < 0.01);
+        assert!((delta_v_from_mass_ratio(ratio, v_e) - 3900.0).abs() < 1e-9);
+        assert_eq!(mass_ratio(0.0, v_e), 1.0);
+    }
+}
@@ -0,0 +1,245 @@
+//! Long-term (orbit-averaged) evolution under a distant third body
+use std::f64::consts::{ PI };
+use crate::{ Body, Orbit, Vector3 };
+
+/// Largest relative miss of the mean motion ratio from p/q still counted as a resonance
+const RESONANCE_TOLERANCE : f64 = 0.01;
+/// Points in the trapezoidal quadrature of `laplace_coefficient`, exact to rounding for the
+/// periodic integrand unless `alpha` is within a few percent of one
+const LAPLACE_SAMPLES : usize = 2048;
+
+impl Orbit {
+
+    /// Unit vector along the angular momentum
+    pub(crate) fn normal(&self) -> Vector3 {
+        self.perifocal_to_inertial(Vector3::k_hat())
+    }
+
+    /// Tisserand parameter `a_p/a + 2 cos i sqrt((a/a_p)(1 - e²))` relative to a perturber on a
+    /// circular orbit of radius `perturber_semimajor` meters in the reference plane, this orbit's
+    /// inclination being measured from that plane. Nearly conserved through close encounters with
+    /// the perturber in the restricted three-body problem. Written with the semi-latus rectum,
+    /// `a(1 - e²)`, so that it holds for parabolic and hyperbolic orbits too.
+    pub fn tisserand_parameter(&self, perturber_semimajor : f64) -> f64 {
+        let inverse_a = self.semimajor_axis().map_or(0.0, |a| 1.0 / a);
+        perturber_semimajor * inverse_a + 2.0 * self.inclination().cos() * (self.parameter() / perturber_semimajor).sqrt()
+    }
+
+    /// `(p, q)` in lowest terms with this orbit's mean motion to `other`'s as `p : q` within
+    /// `RESONANCE_TOLERANCE` (1%) relative, p:q being of order `|p - q|` no more than `max_order`.
+    /// The lowest order match wins, then the smallest ratio terms. None for open orbits or when no
+    /// resonance of that order or lower is near. Io and Europa, for instance, come out 2:1.
+    pub fn mean_motion_resonance(&self, other : &Orbit, body : &Body, max_order : u32) -> Option<(u32, u32)> {
+        self.orbital_period(body)?;
+        other.orbital_period(body)?;
+        let ratio = self.mean_motion(body) / other.mean_motion(body);
+        let near = |p : u32, q : u32| (p as f64 / q as f64 - ratio).abs() <= RESONANCE_TOLERANCE * ratio;
+        if near(1, 1) {
+            return Some((1, 1));
+        }
+        // At order d the faster makes q + d turns to the slower's q, so q = d/(r - 1) for ratio r > 1
+        let (faster, slower) = if ratio > 1.0 { (ratio, 1.0) } else { (1.0, ratio) };
+        let excess = faster / slower - 1.0;
+        (1..=max_order).find_map(|order| {
+            let ideal = order as f64 / excess;
+            let candidates = [ideal.floor().max(1.0), ideal.ceil().max(1.0)];
+            candidates.iter()
+                .map(|&turns| turns as u32)
+                .filter(|&turns| gcd(turns + order, turns) == 1)
+                .map(|turns| if ratio > 1.0 { (turns + order, turns) } else { (turns, turns + order) })
+                .find(|&(p, q)| near(p, q))
+        })
+    }
+
+    /// (node, longitude of periapsis) precession rates in radians/second induced on this orbit by a
+    /// body of `perturber_mass_ratio` times the central body's mass on `perturber` about `body`, from
+    /// Laplace-Lagrange secular theory (Murray & Dermott §7.7):
+    /// `ϖ̇ = -Ω̇ = (n/4) (m'/M) α ᾱ b_3/2^(1)(α)`, with `α` the ratio of the smaller semi-major axis
+    /// to the larger and `ᾱ` equal to `α` for an outer perturber and to one for an inner one.
+    ///
+    /// The disturbing function is expanded to second order in eccentricities and inclinations, so
+    /// both orbits should be nearly circular and nearly coplanar, and this orbit is a test particle
+    /// that doesn't move the perturber. These are the free precession rates; the forced part from the
+    /// perturber's own eccentricity and inclination moves the elements but not the rates. NaN for
+    /// open orbits.
+    pub fn secular_rates_from_perturber(&self, perturber : &Orbit, perturber_mass_ratio : f64, body : &Body) -> (f64, f64) {
+        let (a, a_p) = match (self.orbital_period(body), perturber.orbital_period(body)) {
+            (Some(_), Some(_)) => (self.semimajor_axis().unwrap(), perturber.semimajor_axis().unwrap()),
+            _ => return (f64::NAN, f64::NAN)
+        };
+        let (alpha, alpha_bar) = if a < a_p { (a / a_p, a / a_p) } else { (a_p / a, 1.0) };
+        let rate = self.mean_motion(body) / 4.0 * perturber_mass_ratio * alpha * alpha_bar * laplace_coefficient(alpha, 1, 1.5);
+        (-rate, rate)
+    }
+
+    /// Kozai-Lidov oscillation of this orbit's (inclination, eccentricity) under a distant `perturber`
+    /// of the given mass travelling `perturber_orbit` about the same body, sampled at `steps` evenly
+    /// spaced times over `duration` seconds starting now. Inclination is mutual, relative to the
+    /// perturber's orbital plane.
+    ///
+    /// Uses the test-particle quadrupole secular equations: this orbit's mass is negligible, the
+    /// perturber is far enough out that only the quadrupole of its potential matters, and both orbits
+    /// are averaged over their periods so the semi-major axis is fixed. The perturber's eccentricity
+    /// enters only through the `(1 - e_p²)^(3/2)` timescale factor. Empty if either orbit is open.
+    pub fn kozai_cycle(&self, perturber_orbit : &Orbit, perturber : &Body, body : &Body, steps : usize, duration : f64) -> Vec<(f64, f64)> {
+        let a_p = match (self.orbital_period(body), perturber_orbit.orbital_period(body)) {
+            (Some(_), Some(_)) => perturber_orbit.semimajor_axis().unwrap(),
+            _ => return Vec::new()
+        };
+        let e_p = perturber_orbit.eccentricity();
+        // 1/τ, the inverse Kozai timescale
+        let rate = perturber.k() / (self.mean_motion(body) * a_p.powi(3) * (1.0 - e_p * e_p).powf(1.5));
+
+        // Angle of periapsis measured from the node on the perturber's plane rather than the reference plane
+        let normal = self.normal();
+        let perturber_normal = perturber_orbit.normal();
+        let node = perturber_normal.cross(normal);
+        let periapsis = self.perifocal_to_inertial(Vector3::i_hat());
+        let omega = if node.magnitude() > 1e-12 {
+            let node = node.unit();
+            node.cross(periapsis).dot(normal).atan2(node.dot(periapsis))
+        } else {
+            self.angle_of_periapsis()
+        };
+        let inclination = normal.dot(perturber_normal).clamp(-1.0, 1.0).acos();
+
+        let derivative = |[e, i, w] : [f64; 3]| {
+            let root = (1.0 - e * e).sqrt();
+            let (sin_i, sin_w) = (i.sin(), w.sin());
+            [
+                rate * 15.0 / 8.0 * e * root * sin_i * sin_i * (2.0 * w).sin(),
+                -rate * 15.0 / 16.0 * e * e * (2.0 * w).sin() * (2.0 * i).sin() / root,
+                rate * 0.75 * (2.0 * (1.0 - e * e) + 5.0 * sin_w * sin_w * (e * e - sin_i * sin_i)) / root
+            ]
+        };
+
+        let interval = if steps > 1 { duration / (steps - 1) as f64 } else { 0.0 };
+        // Resolve a secular cycle with plenty of RK4 steps regardless of how coarsely it is sampled
+        let substeps = ((interval * rate * 200.0 / (2.0 * PI)).ceil() as usize).max(1);
+        let h = interval / substeps as f64;
+        let mut state = [self.eccentricity(), inclination, omega];
+        let mut samples = Vec::with_capacity(steps);
+        for _ in 0..steps {
+            samples.push((state[1], state[0]));
+            for _ in 0..substeps {
+                state = rk4_step(&derivative, state, h);
+                state[0] = state[0].clamp(0.0, 1.0 - 1e-12);
+            }
+        }
+        samples
+    }
+}
+
+fn rk4_step<F : Fn([f64; 3]) -> [f64; 3]>(f : &F, y : [f64; 3], h : f64) -> [f64; 3] {
+    let offset = |y : [f64; 3], k : [f64; 3], s : f64| [y[0] + s * k[0], y[1] + s * k[1], y[2] + s * k[2]];
+    let k1 = f(y);
+    let k2 = f(offset(y, k1, h / 2.0));
+    let k3 = f(offset(y, k2, h / 2.0));
+    let k4 = f(offset(y, k3, h));
+    let mut next = y;
+    for j in 0..3 {
+        next[j] += h / 6.0 * (k1[j] + 2.0 * k2[j] + 2.0 * k3[j] + k4[j]);
+    }
+    next
+}
+
+/// Laplace coefficient `b_s^(j)(α) = (1/π) ∫₀^2π cos(jψ) / (1 - 2α cos ψ + α²)^s dψ`, the building
+/// block of the planetary disturbing function, for the ratio `alpha` in `[0, 1)` of the smaller to
+/// the larger semi-major axis. Integrated numerically by the trapezoidal rule, which converges
+/// geometrically for a smooth periodic integrand; NaN for `alpha` outside that range.
+pub fn laplace_coefficient(alpha : f64, j : i32, s : f64) -> f64 {
+    if alpha.is_nan() || !(0.0..1.0).contains(&alpha) {
+        return f64::NAN;
+    }
+    let sum : f64 = (0..LAPLACE_SAMPLES)
+        .map(|k| {
+            let psi = 2.0 * PI * k as f64 / LAPLACE_SAMPLES as f64;
+            (j as f64 * psi).cos() / (1.0 - 2.0 * alpha * psi.cos() + alpha * alpha).powf(s)
+        })
+        .sum();
+    2.0 * sum / LAPLACE_SAMPLES as f64
+}
+
+fn gcd(a : u32, b : u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{ PI };
+    use super::{ laplace_coefficient };
+    use crate::{ earth, Body, Orbit };
+
+    #[test]
+    fn kozai_cycle_conserves_the_kozai_integral() {
+        let planet = earth();
+        let star = Body::builder().mass(1.0e30).radius(7.0e8).build().unwrap();
+        let moon = Orbit::Elliptical { angle_of_periapsis : 0.5, ascending_node : 0.0, inclination : 65f64.to_radians(), periapsis : 7.0e6, eccentricity : 0.01 };
+        let star_orbit = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : 1.0e10 };
+
+        let samples = moon.kozai_cycle(&star_orbit, &star, &planet, 2000, 1.0e9);
+        let integral = |(i, e) : (f64, f64)| (1.0 - e * e).sqrt() * i.cos();
+        for &sample in samples.iter() {
+            assert!((integral(sample) - integral(samples[0])).abs() < 1e-6);
+        }
+        // Starting nearly circular at 65°, eccentricity peaks at sqrt(1 - 5/3 cos² 65°)
+        let peak = samples.iter().map(|&(_, e)| e).fold(0.0, f64::max);
+        let expected = (1.0 - 5.0 / 3.0 * 65f64.to_radians().cos().powi(2)).sqrt();
+        assert!((peak - expected).abs() < 0.01, "{} vs {}", peak, expected);
+    }
+
+    #[test]
+    fn tisserand_parameter_of_the_perturbers_own_orbit_is_three() {
+        let jupiter = 7.785e11;
+        let coorbital = Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis : jupiter };
+        assert!((coorbital.tisserand_parameter(jupiter) - 3.0).abs() < 1e-12);
+        // A Jupiter-family comet sits between 2 and 3
+        let comet = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.2, periapsis : 2.0e11, eccentricity : 0.6 };
+        let expected = jupiter / 5.0e11 + 2.0 * 0.2f64.cos() * (5.0e11 / jupiter * (1.0 - 0.36)).sqrt();
+        assert!((comet.tisserand_parameter(jupiter) - expected).abs() < 1e-12);
+        assert!(expected > 2.0 && expected < 3.0);
+    }
+
+    #[test]
+    fn galilean_moons_are_in_resonance() {
+        let jupiter = Body::builder().mass(1.898e27).radius(7.149e7).build().unwrap();
+        let moon = |semimajor_axis : f64| Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis };
+        let (io, europa, ganymede) = (moon(4.217e8), moon(6.709e8), moon(1.0704e9));
+        assert_eq!(io.mean_motion_resonance(&europa, &jupiter, 3), Some((2, 1)));
+        assert_eq!(ganymede.mean_motion_resonance(&europa, &jupiter, 3), Some((1, 2)));
+        // Second order, 5:3
+        let inner = moon(6.709e8 * 0.6f64.powf(2.0 / 3.0));
+        assert_eq!(inner.mean_motion_resonance(&europa, &jupiter, 2), Some((5, 3)));
+        assert_eq!(inner.mean_motion_resonance(&europa, &jupiter, 1), None);
+
+        let escape = Orbit::Hyperbolic { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 4.0e8, eccentricity : 1.2 };
+        assert_eq!(io.mean_motion_resonance(&escape, &jupiter, 5), None);
+        assert_eq!(io.mean_motion_resonance(&io, &jupiter, 0), Some((1, 1)));
+    }
+
+    #[test]
+    fn laplace_coefficients_match_closed_forms() {
+        // b_1/2^(0)(α) = (4/π) K(α), K(0.5) = 1.685750354812596
+        assert!((laplace_coefficient(0.5, 0, 0.5) - 4.0 / PI * 1.685_750_354_812_596).abs() < 1e-12);
+        // Leading terms for small α: b_3/2^(1) ≈ 3α, and b_s^(j) is even in j
+        assert!((laplace_coefficient(1e-3, 1, 1.5) / 3e-3 - 1.0).abs() < 1e-5);
+        assert_eq!(laplace_coefficient(0.3, -2, 1.5), laplace_coefficient(0.3, 2, 1.5));
+        assert!(laplace_coefficient(1.2, 1, 1.5).is_nan());
+    }
+
+    #[test]
+    fn distant_perturber_precession_matches_the_quadrupole_limit() {
+        let sun = Body::builder().mass(1.989e30).radius(6.957e8).build().unwrap();
+        let orbit = |semimajor_axis : f64| Orbit::Circular { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, semimajor_axis };
+        let (inner, outer) = (orbit(1.0e10), orbit(1.0e12));
+        let mass_ratio = 1e-3;
+        let (node, periapsis) = inner.secular_rates_from_perturber(&outer, mass_ratio, &sun);
+        // (3/4) n (m'/M) (a/a')³ for a far outer perturber
+        let expected = 0.75 * inner.mean_motion(&sun) * mass_ratio * 1e-6;
+        assert!((periapsis / expected - 1.0).abs() < 1e-3, "{} vs {}", periapsis, expected);
+        assert_eq!(node, -periapsis);
+        // Inside out, a distant inner body acts like a J2 of the central mass: (3/4) n (m'/M) (a'/a)²
+        let (_, outside) = outer.secular_rates_from_perturber(&inner, mass_ratio, &sun);
+        assert!((outside / (0.75 * outer.mean_motion(&sun) * mass_ratio * 1e-4) - 1.0).abs() < 1e-3);
+    }
+}
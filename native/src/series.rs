@@ -0,0 +1,57 @@
+//! Iteration-free propagation for nearly circular orbits
+use std::f64::consts::{ PI };
+use crate::{ kepler, offset_time_ms, Anomaly, Body, Orbit };
+
+/// Eccentricity below which `propagate_series` uses the series rather than falling back to the exact solve
+pub const SERIES_MAX_ECCENTRICITY : f64 = 0.2;
+
+impl Orbit {
+
+    /// Anomaly `dt` seconds (negative for earlier) after `start`, approximating the true anomaly by the
+    /// equation of center expanded to fourth order in eccentricity instead of solving Kepler's
+    /// equation. The truncation error in true anomaly is under `2e⁵` radians, 6.4e-4 (0.04°) at the
+    /// e = 0.2 limit and 2e-5 at e = 0.1; the mean anomaly is exact. Orbits at or above
+    /// `SERIES_MAX_ECCENTRICITY`, where the series converges too slowly, and open orbits are
+    /// propagated exactly by `next_anomaly`.
+    pub fn propagate_series(&self, body : &Body, start : &Anomaly, dt : f64) -> Anomaly {
+        let e = self.eccentricity();
+        if e >= SERIES_MAX_ECCENTRICITY || self.orbital_period(body).is_none() {
+            return self.next_anomaly(body, start, dt);
+        }
+        let time_ms = offset_time_ms(start.time_ms, dt);
+        let (_, start_mean) = self.anomalies_from_true(start.true_anomaly);
+        let mean_anomaly = (start_mean + self.mean_motion(body) * dt).rem_euclid(2.0 * PI);
+
+        let (e2, e3, e4) = (e * e, e * e * e, e * e * e * e);
+        let m = mean_anomaly;
+        let true_anomaly = (m
+            + (2.0 * e - e3 / 4.0) * m.sin()
+            + (1.25 * e2 - 11.0 / 24.0 * e4) * (2.0 * m).sin()
+            + 13.0 / 12.0 * e3 * (3.0 * m).sin()
+            + 103.0 / 96.0 * e4 * (4.0 * m).sin()).rem_euclid(2.0 * PI);
+        let (eccentric_anomaly, _) = kepler::elliptical_from_true(e, true_anomaly);
+        Anomaly { time_ms, true_anomaly, mean_anomaly, eccentric_anomaly }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ earth, Anomaly, Orbit };
+
+    #[test]
+    fn series_tracks_the_exact_solution() {
+        let body = earth();
+        for &e in [0.01, 0.1, 0.19].iter() {
+            let orbit = Orbit::Elliptical { angle_of_periapsis : 0.0, ascending_node : 0.0, inclination : 0.0, periapsis : 7.0e6, eccentricity : e };
+            let start = Anomaly::from_true(&orbit, 0.3, 1_000_000);
+            for step in 1..50 {
+                let dt = step as f64 * 137.0;
+                let approximate = orbit.propagate_series(&body, &start, dt);
+                let exact = orbit.next_anomaly(&body, &start, dt);
+                let error = (approximate.true_anomaly - exact.true_anomaly + std::f64::consts::PI).rem_euclid(2.0 * std::f64::consts::PI) - std::f64::consts::PI;
+                assert!(error.abs() < 2.0 * e.powi(5), "e = {}: {}", e, error);
+                assert_eq!(approximate.time_ms, exact.time_ms);
+            }
+        }
+    }
+}
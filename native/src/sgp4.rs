@@ -0,0 +1,446 @@
+//! Two-line element sets and the SGP4 model they are fitted to. TLE elements are mean elements in
+//! the sense of SGP4, so propagating them as two-body motion drifts by kilometers within a day;
+//! `Sgp4` reproduces the predictions they were made for. This is the near-Earth model of Vallado et
+//! al., "Revisiting Spacetrack Report #3" (AIAA 2006-6753), with WGS-72 constants; deep-space
+//! orbits, periods of 225 minutes or more, need its SDP4 extension and are refused.
+use std::f64::consts::{ PI };
+use crate::{ offset_time_ms, Anomaly, Body, Orbit, OrbitError, StateVector, Vector3 };
+use crate::ccsds::{ days_from_civil, invalid };
+
+/// km, WGS-72 equatorial radius
+const RADIUS : f64 = 6378.135;
+/// Square root of GM in Earth radii^1.5/minute, WGS-72
+const XKE : f64 = 0.074_366_916_133_173_4;
+const J2 : f64 = 0.001_082_616;
+const J3 : f64 = -0.000_002_538_81;
+const J4 : f64 = -0.000_001_655_97;
+/// Minutes, where SGP4 hands over to SDP4
+const DEEP_SPACE_PERIOD : f64 = 225.0;
+const KEPLER_ITERATIONS : usize = 10;
+
+/// The elements of a two-line element set
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct Tle {
+    /// milliseconds since the Unix epoch
+    pub epoch_ms : u64,
+    /// Radians
+    pub inclination : f64,
+    /// Radians
+    pub ascending_node : f64,
+    pub eccentricity : f64,
+    /// Radians
+    pub angle_of_periapsis : f64,
+    /// Radians
+    pub mean_anomaly : f64,
+    /// Revolutions/day, Kozai mean motion as printed
+    pub mean_motion : f64,
+    /// Drag term, per Earth radius
+    pub bstar : f64
+}
+
+impl Tle {
+    /// Reads the two 69-column lines. Checksums are not verified.
+    pub fn parse(line1 : &str, line2 : &str) -> Result<Tle, OrbitError> {
+        let field = |line : &str, name : &str, start : usize, end : usize| -> Result<f64, OrbitError> {
+            let text = line.get(start..end).ok_or_else(|| OrbitError::MissingField(name.to_string()))?;
+            text.trim().parse().map_err(|_| invalid(name, text))
+        };
+        if !line1.starts_with('1') || !line2.starts_with('2') {
+            let first = |line : &str| line.chars().next().map(String::from).unwrap_or_default();
+            return Err(invalid("TLE line numbers", &format!("{} / {}", first(line1), first(line2))));
+        }
+
+        let year = field(line1, "epoch year", 18, 20)? as i64;
+        let year = if year < 57 { 2000 + year } else { 1900 + year };
+        let day = field(line1, "epoch day", 20, 32)?;
+        let epoch_ms = (days_from_civil(year, 1, 1) as f64 + day - 1.0) * 86_400_000.0;
+        if epoch_ms < 0.0 {
+            return Err(invalid("epoch year", &year.to_string()));
+        }
+
+        // Assumed decimal point and exponent: " 28098-4" is 0.28098e-4
+        let bstar_text = line1.get(53..61).ok_or_else(|| OrbitError::MissingField("bstar".to_string()))?;
+        // Column 59 can fall inside a multi-byte character even when columns 54 and 61 don't
+        let (mantissa_text, exponent_text) = match (bstar_text.get(..6), bstar_text.get(6..)) {
+            (Some(mantissa), Some(exponent)) => (mantissa, exponent),
+            _ => return Err(invalid("bstar", bstar_text))
+        };
+        let mantissa = mantissa_text.trim().replace(' ', "");
+        let (sign, digits) = match mantissa.strip_prefix('-') {
+            Some(digits) => (-1.0, digits.to_string()),
+            None => (1.0, mantissa.trim_start_matches('+').to_string())
+        };
+        let digits : f64 = format!("0.{}", digits).parse().map_err(|_| invalid("bstar", bstar_text))?;
+        let exponent : i32 = exponent_text.trim().parse().map_err(|_| invalid("bstar", bstar_text))?;
+
+        let eccentricity_text = line2.get(26..33).ok_or_else(|| OrbitError::MissingField("eccentricity".to_string()))?;
+        let eccentricity = format!("0.{}", eccentricity_text.trim()).parse().map_err(|_| invalid("eccentricity", eccentricity_text))?;
+        Ok(Tle {
+            epoch_ms : epoch_ms.round() as u64,
+            inclination : field(line2, "inclination", 8, 16)?.to_radians(),
+            ascending_node : field(line2, "ascending node", 17, 25)?.to_radians(),
+            eccentricity,
+            angle_of_periapsis : field(line2, "argument of perigee", 34, 42)?.to_radians(),
+            mean_anomaly : field(line2, "mean anomaly", 43, 51)?.to_radians(),
+            mean_motion : field(line2, "mean motion", 52, 63)?,
+            bstar : sign * digits * 10f64.powi(exponent)
+        })
+    }
+}
+
+impl Orbit {
+    /// The two-body orbit about `body` and epoch anomaly closest to a TLE: its elements, with the
+    /// mean motion's SGP4 J2 (Kozai) correction removed. Good for display and rough geometry; use
+    /// `Sgp4` to predict positions.
+    pub fn from_tle(line1 : &str, line2 : &str, body : &Body) -> Result<(Orbit, Anomaly), OrbitError> {
+        let tle = Tle::parse(line1, line2)?;
+        let model = Sgp4::new(&tle)?;
+        let orbit = Orbit::from_mean_motion(body, model.mean_motion / 60.0, tle.eccentricity, tle.inclination, tle.ascending_node, tle.angle_of_periapsis);
+        let anomaly = Anomaly::from_mean(&orbit, tle.mean_anomaly, tle.epoch_ms);
+        Ok((orbit, anomaly))
+    }
+}
+
+/// SGP4 initialized from a TLE
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Sgp4 {
+    epoch_ms : u64,
+    inclination : f64,
+    ascending_node : f64,
+    eccentricity : f64,
+    angle_of_periapsis : f64,
+    mean_anomaly : f64,
+    bstar : f64,
+    /// Radians/minute, Brouwer
+    mean_motion : f64,
+    /// Perigee low enough that the higher order drag terms are dropped
+    simple : bool,
+    con41 : f64,
+    x1mth2 : f64,
+    x7thm1 : f64,
+    eta : f64,
+    cc1 : f64,
+    cc4 : f64,
+    cc5 : f64,
+    d2 : f64,
+    d3 : f64,
+    d4 : f64,
+    delmo : f64,
+    sinmao : f64,
+    mdot : f64,
+    argpdot : f64,
+    nodedot : f64,
+    omgcof : f64,
+    xmcof : f64,
+    nodecf : f64,
+    t2cof : f64,
+    t3cof : f64,
+    t4cof : f64,
+    t5cof : f64,
+    xlcof : f64,
+    aycof : f64
+}
+
+impl Sgp4 {
+    /// Precomputes the secular and drag coefficients. `OrbitError::Unreachable` for deep-space
+    /// orbits and for elements that describe no bound orbit.
+    pub fn new(tle : &Tle) -> Result<Sgp4, OrbitError> {
+        let (e, inclination, argp, mo) = (tle.eccentricity, tle.inclination, tle.angle_of_periapsis, tle.mean_anomaly);
+        let j3oj2 = J3 / J2;
+        let no_kozai = tle.mean_motion * 2.0 * PI / 1440.0;
+        if !(0.0..1.0).contains(&e) || no_kozai.is_nan() || no_kozai <= 0.0 {
+            return Err(OrbitError::Unreachable("TLE elements describe no bound orbit".to_string()));
+        }
+
+        // Recover the Brouwer mean motion and semi-major axis from the Kozai mean motion
+        let omeosq = 1.0 - e * e;
+        let rteosq = omeosq.sqrt();
+        let (sinio, cosio) = inclination.sin_cos();
+        let cosio2 = cosio * cosio;
+        let ak = (XKE / no_kozai).powf(2.0 / 3.0);
+        let d1 = 0.75 * J2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let del = d1 / (ak * ak);
+        let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        let del = d1 / (adel * adel);
+        let mean_motion = no_kozai / (1.0 + del);
+        let ao = (XKE / mean_motion).powf(2.0 / 3.0);
+        if 2.0 * PI / mean_motion >= DEEP_SPACE_PERIOD {
+            return Err(OrbitError::Unreachable("deep-space orbits need SDP4".to_string()));
+        }
+
+        let po = ao * omeosq;
+        let con42 = 1.0 - 5.0 * cosio2;
+        let con41 = -con42 - cosio2 - cosio2;
+        let posq = po * po;
+        let rp = ao * (1.0 - e);
+        let simple = rp < 220.0 / RADIUS + 1.0;
+
+        // Atmospheric density fit, lowered for perigees under 156 km
+        let mut sfour = 78.0 / RADIUS + 1.0;
+        let mut qzms24 = ((120.0 - 78.0) / RADIUS).powi(4);
+        let perigee = (rp - 1.0) * RADIUS;
+        if perigee < 156.0 {
+            let s = if perigee < 98.0 { 20.0 } else { perigee - 78.0 };
+            qzms24 = ((120.0 - s) / RADIUS).powi(4);
+            sfour = s / RADIUS + 1.0;
+        }
+        let pinvsq = 1.0 / posq;
+        let tsi = 1.0 / (ao - sfour);
+        let eta = ao * e * tsi;
+        let etasq = eta * eta;
+        let eeta = e * eta;
+        let psisq = (1.0 - etasq).abs();
+        let coef = qzms24 * tsi.powi(4);
+        let coef1 = coef / psisq.powf(3.5);
+        let cc2 = coef1 * mean_motion * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+            + 0.375 * J2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let cc1 = tle.bstar * cc2;
+        let cc3 = if e > 1e-4 { -2.0 * coef * tsi * j3oj2 * mean_motion * sinio / e } else { 0.0 };
+        let x1mth2 = 1.0 - cosio2;
+        let cc4 = 2.0 * mean_motion * coef1 * ao * omeosq * (eta * (2.0 + 0.5 * etasq) + e * (0.5 + 2.0 * etasq)
+            - J2 * tsi / (ao * psisq) * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+                + 0.75 * x1mth2 * (2.0 * etasq - eeta * (1.0 + etasq)) * (2.0 * argp).cos()));
+        let cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+
+        // Secular rates from J2 and J4
+        let cosio4 = cosio2 * cosio2;
+        let temp1 = 1.5 * J2 * pinvsq * mean_motion;
+        let temp2 = 0.5 * temp1 * J2 * pinvsq;
+        let temp3 = -0.468_75 * J4 * pinvsq * pinvsq * mean_motion;
+        let mdot = mean_motion + 0.5 * temp1 * rteosq * con41 + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot = -0.5 * temp1 * con42 + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1 = -temp1 * cosio;
+        let nodedot = xhdot1 + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+
+        let omgcof = tle.bstar * cc3 * argp.cos();
+        let xmcof = if e > 1e-4 { -2.0 / 3.0 * coef * tle.bstar / eeta } else { 0.0 };
+        let nodecf = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof = 1.5 * cc1;
+        // Guard the division for inclinations of exactly 180°
+        let xlcof_denominator = if (cosio + 1.0).abs() > 1.5e-12 { 1.0 + cosio } else { 1.5e-12 };
+        let xlcof = -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio) / xlcof_denominator;
+        let aycof = -0.5 * j3oj2 * sinio;
+
+        let (mut d2, mut d3, mut d4, mut t3cof, mut t4cof, mut t5cof) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        if !simple {
+            let cc1sq = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2 * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        Ok(Sgp4 {
+            epoch_ms : tle.epoch_ms,
+            inclination,
+            ascending_node : tle.ascending_node,
+            eccentricity : e,
+            angle_of_periapsis : argp,
+            mean_anomaly : mo,
+            bstar : tle.bstar,
+            mean_motion,
+            simple,
+            con41,
+            x1mth2,
+            x7thm1 : 7.0 * cosio2 - 1.0,
+            eta,
+            cc1,
+            cc4,
+            cc5,
+            d2,
+            d3,
+            d4,
+            delmo : (1.0 + eta * mo.cos()).powi(3),
+            sinmao : mo.sin(),
+            mdot,
+            argpdot,
+            nodedot,
+            omgcof,
+            xmcof,
+            nodecf,
+            t2cof,
+            t3cof,
+            t4cof,
+            t5cof,
+            xlcof,
+            aycof
+        })
+    }
+
+    /// TEME position and velocity `dt` seconds after the TLE epoch, in meters and meters/second.
+    /// `OrbitError::Unreachable` once drag has driven the orbit into the Earth or its eccentricity
+    /// out of range.
+    pub fn state_at(&self, dt : f64) -> Result<StateVector, OrbitError> {
+        let t = dt / 60.0;
+        let t2 = t * t;
+
+        // Secular gravity and drag
+        let xmdf = self.mean_anomaly + self.mdot * t;
+        let argpdf = self.angle_of_periapsis + self.argpdot * t;
+        let nodedf = self.ascending_node + self.nodedot * t;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let mut nodem = nodedf + self.nodecf * t2;
+        let mut tempa = 1.0 - self.cc1 * t;
+        let mut tempe = self.bstar * self.cc4 * t;
+        let mut templ = self.t2cof * t2;
+        if !self.simple {
+            let delomg = self.omgcof * t;
+            let delm = self.xmcof * ((1.0 + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            mm = xmdf + delomg + delm;
+            argpm = argpdf - delomg - delm;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa -= self.d2 * t2 + self.d3 * t3 + self.d4 * t4;
+            tempe += self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ += self.t3cof * t3 + t4 * (self.t4cof + t * self.t5cof);
+        }
+        let am = (XKE / self.mean_motion).powf(2.0 / 3.0) * tempa * tempa;
+        let nm = XKE / am.powf(1.5);
+        let em = self.eccentricity - tempe;
+        if !(-0.001..1.0).contains(&em) {
+            return Err(OrbitError::Unreachable(format!("eccentricity {} after {} s", em, dt)));
+        }
+        let em = em.max(1e-6);
+        mm += self.mean_motion * templ;
+        let xlm = (mm + argpm + nodem) % (2.0 * PI);
+        nodem %= 2.0 * PI;
+        argpm %= 2.0 * PI;
+        let mm = (xlm - argpm - nodem) % (2.0 * PI);
+
+        // Long period periodics
+        let (sinip, cosip) = self.inclination.sin_cos();
+        let axnl = em * argpm.cos();
+        let temp = 1.0 / (am * (1.0 - em * em));
+        let aynl = em * argpm.sin() + temp * self.aycof;
+        let xl = mm + argpm + nodem + temp * self.xlcof * axnl;
+
+        // Kepler's equation in terms of the eccentricity vector components
+        let u = (xl - nodem) % (2.0 * PI);
+        let mut eo1 = u;
+        let (mut sineo1, mut coseo1) = eo1.sin_cos();
+        for _ in 0..KEPLER_ITERATIONS {
+            let (s, c) = eo1.sin_cos();
+            sineo1 = s;
+            coseo1 = c;
+            let step = (u - aynl * coseo1 + axnl * sineo1 - eo1) / (1.0 - coseo1 * axnl - sineo1 * aynl);
+            let step = step.clamp(-0.95, 0.95);
+            eo1 += step;
+            if step.abs() < 1e-12 {
+                break;
+            }
+        }
+
+        // Short period periodics
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        if pl < 0.0 {
+            return Err(OrbitError::Unreachable(format!("semi-latus rectum negative after {} s", dt)));
+        }
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let su = sinu.atan2(cosu);
+        let sin2u = 2.0 * cosu * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * J2 * temp;
+        let temp2 = temp1 * temp;
+
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * self.con41) + 0.5 * temp1 * self.x1mth2 * cos2u;
+        let su = su - 0.25 * temp2 * self.x7thm1 * sin2u;
+        let xnode = nodem + 1.5 * temp2 * cosip * sin2u;
+        let xinc = self.inclination + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * self.x1mth2 * sin2u / XKE;
+        let rvdot = rvdotl + nm * temp1 * (self.x1mth2 * cos2u + 1.5 * self.con41) / XKE;
+        if mrt < 1.0 {
+            return Err(OrbitError::Unreachable(format!("decayed after {} s", dt)));
+        }
+
+        // Orientation vectors: u toward the object, v along its motion
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let (xmx, xmy) = (-snod * cosi, cnod * cosi);
+        let toward = Vector3::new(xmx * sinsu + cnod * cossu, xmy * sinsu + snod * cossu, sini * sinsu);
+        let along = Vector3::new(xmx * cossu - cnod * sinsu, xmy * cossu - snod * sinsu, sini * cossu);
+        let meters_per_second = RADIUS * 1e3 * XKE / 60.0;
+        Ok(StateVector::new(
+            toward * (mrt * RADIUS * 1e3),
+            (toward * mvt + along * rvdot) * meters_per_second,
+            offset_time_ms(self.epoch_ms, dt)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE1 : &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+    const LINE2 : &str = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+    #[test]
+    fn reads_the_element_set() {
+        let tle = Tle::parse(LINE1, LINE2).unwrap();
+        assert!((tle.bstar - 0.28098e-4).abs() < 1e-15);
+        assert!((tle.eccentricity - 0.185_966_7).abs() < 1e-15);
+        assert!((tle.inclination.to_degrees() - 34.2682).abs() < 1e-12);
+        assert!((tle.mean_motion - 10.824_191_57).abs() < 1e-12);
+        // 2000 day 179.78495062
+        let expected = (days_from_civil(2000, 1, 1) as f64 + 178.784_950_62) * 86_400_000.0;
+        assert!((tle.epoch_ms as f64 - expected).abs() < 1.0);
+        assert!(Tle::parse(LINE2, LINE1).is_err());
+    }
+
+    #[test]
+    fn rejects_non_ascii_without_panicking() {
+        assert!(Tle::parse(&format!("é{}", &LINE1[1..]), LINE2).is_err());
+        assert!(Tle::parse(LINE1, "").is_err());
+        // A two-byte character over columns 59-60 leaves the B* field's outer boundaries intact
+        let split_bstar = format!("{}é{}", &LINE1[..58], &LINE1[60..]);
+        assert_eq!(split_bstar.len(), LINE1.len());
+        assert!(matches!(Tle::parse(&split_bstar, LINE2), Err(OrbitError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn matches_the_verification_ephemeris() {
+        // Satellite 00005 from the AIAA 2006-6753 verification set, km and km/s
+        let model = Sgp4::new(&Tle::parse(LINE1, LINE2).unwrap()).unwrap();
+        let cases = [
+            (0.0, [7022.46529266, -1400.08296755, 0.03995155], [1.893841015, 6.405893759, 4.534807250]),
+            (360.0, [-7154.03120202, -3783.17682504, -3536.19412294], [4.741887409, -4.151817765, -2.093935425])
+        ];
+        for &(minutes, position, velocity) in cases.iter() {
+            let state = model.state_at(minutes * 60.0).unwrap();
+            let expected = Vector3::new(position[0], position[1], position[2]) * 1e3;
+            assert!((state.position() - expected).magnitude() < 1e-2, "{} min: {:?}", minutes, state.position());
+            let expected = Vector3::new(velocity[0], velocity[1], velocity[2]) * 1e3;
+            assert!((state.velocity() - expected).magnitude() < 1e-5, "{} min: {:?}", minutes, state.velocity());
+        }
+    }
+
+    #[test]
+    fn tle_orbit_is_near_the_sgp4_state() {
+        let body = Body::builder().gravitational_parameter(398_600.8e9).radius(RADIUS * 1e3).build().unwrap();
+        let (orbit, anomaly) = Orbit::from_tle(LINE1, LINE2, &body).unwrap();
+        let two_body = orbit.state_vector(&body, &anomaly);
+        let sgp4 = Sgp4::new(&Tle::parse(LINE1, LINE2).unwrap()).unwrap().state_at(0.0).unwrap();
+        assert_eq!(two_body.time_ms(), sgp4.time_ms());
+        // Mean against osculating elements: kilometers apart, not thousands
+        assert!((two_body.position() - sgp4.position()).magnitude() < 20e3);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{ Body, Orbit, OrbitError, Vector3 };
+
+/// Coordinate frame a state is expressed in, all centered on the body
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub enum Frame {
+    /// Inertial, z along the body's pole, as every orbit in this crate is oriented
+    Eci,
+    /// Body-fixed, turned `gmst` radians from `Eci` about z and turning at `rotation_rate`
+    /// radians/second, so velocities are relative to the rotating surface
+    Ecef { rotation_rate : f64 },
+    /// Inertial, x toward periapsis and z along the orbit normal of the orbit with these angles
+    /// (radians)
+    Perifocal { inclination : f64, ascending_node : f64, angle_of_periapsis : f64 }
+}
+
+/// Position and velocity at an instant, in `frame`
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct StateVector {
+    position : Vector3,
+    velocity : Vector3,
+    time_ms : u64,
+    frame : Frame
+}
+
+impl StateVector {
+    /// An inertial (`Frame::Eci`) state, the frame every orbit method works in
+    pub fn new(position : Vector3, velocity : Vector3, time_ms : u64) -> StateVector {
+        StateVector::in_frame(position, velocity, time_ms, Frame::Eci)
+    }
+
+    pub fn in_frame(position : Vector3, velocity : Vector3, time_ms : u64, frame : Frame) -> StateVector {
+        StateVector { position, velocity, time_ms, frame }
+    }
+
+    /// meters
+    pub fn position(&self) -> Vector3 { self.position }
+    /// meters/second
+    pub fn velocity(&self) -> Vector3 { self.velocity }
+    /// milliseconds, on the same clock as `Anomaly::time_ms`
+    pub fn time_ms(&self) -> u64 { self.time_ms }
+    pub fn frame(&self) -> Frame { self.frame }
+
+    /// Meters from the origin
+    pub fn radius(&self) -> f64 { self.position.magnitude() }
+    /// Meters/second
+    pub fn speed(&self) -> f64 { self.velocity.magnitude() }
+
+    /// The orbit this state lies on, as `Orbit::from_state_vector` reads it
+    pub fn orbit(&self, body : &Body) -> Result<Orbit, OrbitError> {
+        Orbit::from_state_vector(self, body)
+    }
+
+    /// This state in `Frame::Eci`, where every orbit method works. Perifocal states are a fixed
+    /// rotation away; body-fixed ones need the body's angle at this time, so they fail with
+    /// `OrbitError::InvalidValue` and have to come over by `transform_to` with a `gmst`.
+    pub fn inertial(&self) -> Result<StateVector, OrbitError> {
+        match self.frame {
+            Frame::Eci => Ok(*self),
+            Frame::Perifocal { .. } => Ok(self.transform_to(Frame::Eci, 0.0)),
+            Frame::Ecef { .. } => Err(OrbitError::InvalidValue { keyword : "frame".to_string(), value : format!("{:?}", self.frame) })
+        }
+    }
+
+    /// This state in the `target` frame, by way of `Frame::Eci`, `gmst` radians being the angle from
+    /// the inertial x axis to the body-fixed one at this state's time. Perifocal frames are a fixed
+    /// rotation; body-fixed ones also take out or put back the velocity of the turning frame,
+    /// `ω × r`. `gmst` is ignored unless one side is `Frame::Ecef`.
+    pub fn transform_to(&self, target : Frame, gmst : f64) -> StateVector {
+        let (position, velocity) = match self.frame {
+            Frame::Eci => (self.position, self.velocity),
+            Frame::Ecef { rotation_rate } => {
+                let turning = Vector3::k_hat().cross(self.position) * rotation_rate;
+                (self.position.rotate_z(gmst), (self.velocity + turning).rotate_z(gmst))
+            },
+            Frame::Perifocal { inclination, ascending_node, angle_of_periapsis } => {
+                let rotate = |v : Vector3| v.rotate_z(angle_of_periapsis).rotate_x(inclination).rotate_z(ascending_node);
+                (rotate(self.position), rotate(self.velocity))
+            }
+        };
+        let (position, velocity) = match target {
+            Frame::Eci => (position, velocity),
+            Frame::Ecef { rotation_rate } => {
+                let turning = Vector3::k_hat().cross(position) * rotation_rate;
+                (position.rotate_z(-gmst), (velocity - turning).rotate_z(-gmst))
+            },
+            Frame::Perifocal { inclination, ascending_node, angle_of_periapsis } => {
+                let rotate = |v : Vector3| v.rotate_z(-ascending_node).rotate_x(-inclination).rotate_z(-angle_of_periapsis);
+                (rotate(position), rotate(velocity))
+            }
+        };
+        StateVector::in_frame(position, velocity, self.time_ms, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ Anomaly };
+
+    #[test]
+    fn frames_round_trip_through_eci() {
+        let body = Body::builder().mass(5.972e24).radius(6.378e6).rotation_period(86_164.1).build().unwrap();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 };
+        let state = orbit.state_vector(&body, &Anomaly::from_true(&orbit, 0.5, 0));
+        assert_eq!(state.frame(), Frame::Eci);
+
+        // In its own perifocal frame the orbit lies in the xy plane with periapsis along x
+        let perifocal = Frame::Perifocal { inclination : 0.9, ascending_node : 2.1, angle_of_periapsis : 0.7 };
+        let flat = state.transform_to(perifocal, 0.0);
+        assert!(flat.position().z.abs() < 1e-6 && flat.velocity().z.abs() < 1e-9);
+        assert!((flat.position().y.atan2(flat.position().x) - 0.5).abs() < 1e-12);
+
+        let fixed = Frame::Ecef { rotation_rate : body.rotation_rate() };
+        let ecef = flat.transform_to(fixed, 1.3);
+        // The frame turns with the body, so the state slows by ω × r relative to it
+        let turning = Vector3::k_hat().cross(state.position()) * body.rotation_rate();
+        assert!((ecef.velocity().magnitude() - (state.velocity() - turning).magnitude()).abs() < 1e-9);
+        let back = ecef.transform_to(Frame::Eci, 1.3);
+        assert_eq!(back.frame(), Frame::Eci);
+        assert!((back.position() - state.position()).magnitude() < 1e-6);
+        assert!((back.velocity() - state.velocity()).magnitude() < 1e-9);
+    }
+
+    #[test]
+    fn orbits_read_the_frame_tag() {
+        let body = Body::builder().mass(5.972e24).radius(6.378e6).rotation_period(86_164.1).build().unwrap();
+        let orbit = Orbit::Elliptical { angle_of_periapsis : 0.7, ascending_node : 2.1, inclination : 0.9, periapsis : 7.0e6, eccentricity : 0.3 };
+        let state = orbit.state_vector(&body, &Anomaly::from_true(&orbit, 0.5, 0));
+        let perifocal = state.transform_to(Frame::Perifocal { inclination : 0.9, ascending_node : 2.1, angle_of_periapsis : 0.7 }, 0.0);
+
+        let (rebuilt, anomaly) = Orbit::from_burnout(&perifocal, &body).unwrap();
+        assert!((rebuilt.inclination() - 0.9).abs() < 1e-9);
+        assert!((rebuilt.ascending_node() - 2.1).abs() < 1e-9);
+        assert!((rebuilt.angle_of_periapsis() - 0.7).abs() < 1e-9);
+        assert!((anomaly.true_anomaly - 0.5).abs() < 1e-9);
+        assert!((perifocal.orbit(&body).unwrap().periapsis() - 7.0e6).abs() < 1e-3);
+
+        let ecef = state.transform_to(Frame::Ecef { rotation_rate : body.rotation_rate() }, 1.3);
+        assert!(Orbit::from_state_vector(&ecef, &body).is_err());
+        assert!(crate::integrate_adaptive(&ecef, &body, 60.0, 1e-10).is_err());
+        let (from_perifocal, _) = crate::integrate_adaptive(&perifocal, &body, 600.0, 1e-12).unwrap();
+        let (from_eci, _) = crate::integrate_adaptive(&state, &body, 600.0, 1e-12).unwrap();
+        assert_eq!(from_perifocal.last().unwrap().frame(), Frame::Eci);
+        assert!((from_perifocal.last().unwrap().position() - from_eci.last().unwrap().position()).magnitude() < 1e-3);
+        assert!((crate::integrate_symplectic(&perifocal, &body, 1.0, 10).unwrap()[10].position() - crate::integrate_symplectic(&state, &body, 1.0, 10).unwrap()[10].position()).magnitude() < 1e-3);
+    }
+}
@@ -0,0 +1,240 @@
+//! Reference conversions between inertial states and orbital elements, behind the `test_vectors`
+//! feature, for checking other implementations against this crate and this crate against the
+//! literature. Values are SI (meters, meters/second, radians). Equatorial and circular orbits follow
+//! the conventions of `Orbit::from_state_vector`: an undefined ascending node is zero, and an
+//! undefined angle of periapsis is zero with true anomaly measured from the node.
+use std::f64::consts::{ PI };
+use crate::{ Anomaly, Body, Orbit, StateVector, Vector3, G };
+
+const DEGREE : f64 = PI / 180.0;
+const KILOMETER : f64 = 1000.0;
+/// Earth's gravitational parameter in m³/s², as used by Vallado
+const EARTH_GM : f64 = 3.986_004_418e14;
+
+/// Which side of a vector is the given input; the tolerances apply to the other side
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub enum Direction {
+    /// Published state, elements derived from it
+    StateToElements,
+    /// Published elements, state derived from them
+    ElementsToState,
+    /// Exact in both directions, for constructed cases
+    RoundTrip
+}
+
+/// How far a conversion may land from the expected values, None for the side of the vector that
+/// is given rather than derived (see `Direction`), where there is nothing to check
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct Tolerances {
+    /// meters, for periapsis
+    pub length : Option<f64>,
+    pub eccentricity : Option<f64>,
+    /// radians, for every angle
+    pub angle : Option<f64>,
+    /// meters
+    pub position : Option<f64>,
+    /// meters/second
+    pub velocity : Option<f64>
+}
+
+/// One inertial state and the elements of the orbit through it
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct TestVector {
+    pub name : &'static str,
+    /// Where the values come from
+    pub source : &'static str,
+    /// m³/s²
+    pub gravitational_parameter : f64,
+    /// meters
+    pub position : Vector3,
+    /// meters/second
+    pub velocity : Vector3,
+    /// meters
+    pub periapsis : f64,
+    pub eccentricity : f64,
+    pub inclination : f64,
+    pub ascending_node : f64,
+    pub angle_of_periapsis : f64,
+    pub true_anomaly : f64,
+    pub direction : Direction,
+    pub tolerances : Tolerances
+}
+
+impl TestVector {
+    /// A body with the vector's gravitational parameter and an arbitrary radius
+    pub fn body(&self) -> Body {
+        Body { mass : self.gravitational_parameter / G, radius : 1.0, rotation_period : None, flattening : 0.0, j2 : None, j3 : None, j4 : None }
+    }
+
+    /// The state, at time zero
+    pub fn state(&self) -> StateVector {
+        StateVector::new(self.position, self.velocity, 0)
+    }
+
+    /// The expected orbit
+    pub fn orbit(&self) -> Orbit {
+        Orbit::from_elements(self.periapsis, self.eccentricity, self.inclination, self.ascending_node, self.angle_of_periapsis)
+    }
+
+    /// The expected anomaly, at time zero
+    pub fn anomaly(&self) -> Anomaly {
+        Anomaly::from_true(&self.orbit(), self.true_anomaly, 0)
+    }
+}
+
+const EXACT : Tolerances = Tolerances { length : Some(1e-2), eccentricity : Some(1e-9), angle : Some(1e-9), position : Some(1e-2), velocity : Some(1e-5) };
+
+/// Vallado's worked examples followed by constructed circular, equatorial, retrograde and
+/// hyperbolic cases
+pub const TEST_VECTORS : &[TestVector] = &[
+    TestVector {
+        name : "rv2coe",
+        source : "Vallado, Fundamentals of Astrodynamics and Applications, 4th ed., Example 2-5",
+        gravitational_parameter : EARTH_GM,
+        position : Vector3 { x : 6524.834 * KILOMETER, y : 6862.875 * KILOMETER, z : 6448.296 * KILOMETER },
+        velocity : Vector3 { x : 4.901_327 * KILOMETER, y : 5.533_756 * KILOMETER, z : -1.976_341 * KILOMETER },
+        // p = 11 067.790 km
+        periapsis : 11_067.790 * KILOMETER / 1.832_853,
+        eccentricity : 0.832_853,
+        inclination : 87.870 * DEGREE,
+        ascending_node : 227.89 * DEGREE,
+        angle_of_periapsis : 53.38 * DEGREE,
+        true_anomaly : 92.335 * DEGREE,
+        direction : Direction::StateToElements,
+        tolerances : Tolerances { length : Some(10.0), eccentricity : Some(1e-6), angle : Some(0.01 * DEGREE), position : None, velocity : None }
+    },
+    TestVector {
+        name : "coe2rv",
+        source : "Vallado, Fundamentals of Astrodynamics and Applications, 4th ed., Example 2-6",
+        gravitational_parameter : EARTH_GM,
+        position : Vector3 { x : 6525.368 * KILOMETER, y : 6861.532 * KILOMETER, z : 6449.119 * KILOMETER },
+        velocity : Vector3 { x : 4.902_279 * KILOMETER, y : 5.533_140 * KILOMETER, z : -1.975_710 * KILOMETER },
+        periapsis : 11_067.790 * KILOMETER / 1.832_85,
+        eccentricity : 0.832_85,
+        inclination : 87.87 * DEGREE,
+        ascending_node : 227.89 * DEGREE,
+        angle_of_periapsis : 53.38 * DEGREE,
+        true_anomaly : 92.335 * DEGREE,
+        direction : Direction::ElementsToState,
+        tolerances : Tolerances { length : None, eccentricity : None, angle : None, position : Some(1.0), velocity : Some(1e-3) }
+    },
+    TestVector {
+        name : "circular equatorial",
+        source : "constructed",
+        gravitational_parameter : EARTH_GM,
+        position : Vector3 { x : 7000.0 * KILOMETER, y : 0.0, z : 0.0 },
+        velocity : Vector3 { x : 0.0, y : 7.546_053_290 * KILOMETER, z : 0.0 },
+        periapsis : 7000.0 * KILOMETER,
+        eccentricity : 0.0,
+        inclination : 0.0,
+        ascending_node : 0.0,
+        angle_of_periapsis : 0.0,
+        true_anomaly : 0.0,
+        direction : Direction::RoundTrip,
+        tolerances : EXACT
+    },
+    TestVector {
+        name : "circular inclined",
+        source : "constructed, argument of latitude 60°",
+        gravitational_parameter : EARTH_GM,
+        position : Vector3 { x : 887.785388 * KILOMETER, y : 5462.310601 * KILOMETER, z : 4286.607050 * KILOMETER },
+        velocity : Vector3 { x : -6.993_506_331 * KILOMETER, y : -0.957_039_407 * KILOMETER, z : 2.667_932_726 * KILOMETER },
+        periapsis : 7000.0 * KILOMETER,
+        eccentricity : 0.0,
+        inclination : 45.0 * DEGREE,
+        ascending_node : 30.0 * DEGREE,
+        angle_of_periapsis : 0.0,
+        true_anomaly : 60.0 * DEGREE,
+        direction : Direction::RoundTrip,
+        tolerances : EXACT
+    },
+    TestVector {
+        name : "elliptical equatorial",
+        source : "constructed, longitude of periapsis 40°",
+        gravitational_parameter : EARTH_GM,
+        position : Vector3 { x : 2448.820970 * KILOMETER, y : 6728.080320 * KILOMETER, z : 0.0 },
+        velocity : Vector3 { x : -7.358_718_747 * KILOMETER, y : 3.411_421_179 * KILOMETER, z : 0.0 },
+        periapsis : 7000.0 * KILOMETER,
+        eccentricity : 0.2,
+        inclination : 0.0,
+        ascending_node : 0.0,
+        angle_of_periapsis : 40.0 * DEGREE,
+        true_anomaly : 30.0 * DEGREE,
+        direction : Direction::RoundTrip,
+        tolerances : EXACT
+    },
+    TestVector {
+        name : "elliptical retrograde equatorial",
+        source : "constructed, angles clockwise seen from +z",
+        gravitational_parameter : EARTH_GM,
+        position : Vector3 { x : 2448.820970 * KILOMETER, y : -6728.080320 * KILOMETER, z : 0.0 },
+        velocity : Vector3 { x : -7.358_718_747 * KILOMETER, y : -3.411_421_179 * KILOMETER, z : 0.0 },
+        periapsis : 7000.0 * KILOMETER,
+        eccentricity : 0.2,
+        inclination : PI,
+        ascending_node : 0.0,
+        angle_of_periapsis : 40.0 * DEGREE,
+        true_anomaly : 30.0 * DEGREE,
+        direction : Direction::RoundTrip,
+        tolerances : EXACT
+    },
+    TestVector {
+        name : "hyperbolic",
+        source : "constructed, inbound before periapsis",
+        gravitational_parameter : EARTH_GM,
+        position : Vector3 { x : -2459.489886 * KILOMETER, y : -8268.613507 * KILOMETER, z : 2227.390997 * KILOMETER },
+        velocity : Vector3 { x : 9.251_548_838 * KILOMETER, y : 1.694_444_276 * KILOMETER, z : -5.430_114_488 * KILOMETER },
+        periapsis : 7000.0 * KILOMETER,
+        eccentricity : 1.5,
+        inclination : 30.0 * DEGREE,
+        ascending_node : 100.0 * DEGREE,
+        angle_of_periapsis : 200.0 * DEGREE,
+        true_anomaly : -50.0 * DEGREE,
+        direction : Direction::RoundTrip,
+        tolerances : EXACT
+    }
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maneuvers::{ wrap_angle };
+
+    #[test]
+    fn conversions_match_the_vectors() {
+        for vector in TEST_VECTORS.iter() {
+            let (body, tolerances) = (vector.body(), vector.tolerances);
+            match vector.direction {
+                Direction::StateToElements => assert!(tolerances.position.is_none() && tolerances.velocity.is_none(), "{}", vector.name),
+                Direction::ElementsToState => assert!(tolerances.length.is_none() && tolerances.angle.is_none(), "{}", vector.name),
+                Direction::RoundTrip => ()
+            }
+            if vector.direction != Direction::ElementsToState {
+                let (orbit, anomaly) = Orbit::from_burnout(&vector.state(), &body).unwrap();
+                assert!((orbit.periapsis() - vector.periapsis).abs() < tolerances.length.unwrap(), "{}", vector.name);
+                assert!((orbit.eccentricity() - vector.eccentricity).abs() < tolerances.eccentricity.unwrap(), "{}", vector.name);
+                let angles = [
+                    (orbit.inclination(), vector.inclination),
+                    (orbit.ascending_node(), vector.ascending_node),
+                    (orbit.angle_of_periapsis(), vector.angle_of_periapsis),
+                    (anomaly.true_anomaly, vector.true_anomaly)
+                ];
+                for &(actual, expected) in angles.iter() {
+                    assert!(wrap_angle(actual - expected).abs() < tolerances.angle.unwrap(), "{}: {} vs {}", vector.name, actual, expected);
+                }
+            }
+            if vector.direction != Direction::StateToElements {
+                let state = vector.orbit().state_vector(&body, &vector.anomaly());
+                assert!((state.position() - vector.position).magnitude() < tolerances.position.unwrap(), "{}", vector.name);
+                assert!((state.velocity() - vector.velocity).magnitude() < tolerances.velocity.unwrap(), "{}", vector.name);
+            }
+        }
+    }
+}
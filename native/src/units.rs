@@ -0,0 +1,87 @@
+//! Typed physical quantities from the `uom` crate at the API boundary, behind the `uom` feature.
+//! Fields and the math underneath stay plain SI `f64`s; these only convert on the way in and out,
+//! so a kilometre or a degree handed in is caught and scaled rather than silently misread.
+use uom::si::f64::{ Angle, Length, Mass, Time, Velocity };
+use uom::si::angle::{ radian };
+use uom::si::length::{ meter };
+use uom::si::mass::{ kilogram };
+use uom::si::time::{ second };
+use uom::si::velocity::{ meter_per_second };
+use crate::{ Body, Orbit, StateVector };
+
+impl Body {
+    /// A `Body` from typed mass, equatorial radius and sidereal rotation period, None for a body
+    /// treated as not rotating. No zonal harmonics; set `j2`..`j4` afterwards if needed.
+    pub fn from_quantities(mass : Mass, radius : Length, rotation_period : Option<Time>, flattening : f64) -> Body {
+        Body {
+            mass : mass.get::<kilogram>(),
+            radius : radius.get::<meter>(),
+            rotation_period : rotation_period.map(|period| period.get::<second>()),
+            flattening,
+            j2 : None,
+            j3 : None,
+            j4 : None
+        }
+    }
+
+    /// `mass` as a typed quantity
+    pub fn mass_quantity(&self) -> Mass { Mass::new::<kilogram>(self.mass) }
+    /// `radius`, equatorial, as a typed quantity
+    pub fn radius_quantity(&self) -> Length { Length::new::<meter>(self.radius) }
+}
+
+impl Orbit {
+
+    /// `from_elements` with typed periapsis and angles
+    pub fn from_quantities(periapsis : Length, eccentricity : f64, inclination : Angle, ascending_node : Angle, angle_of_periapsis : Angle) -> Orbit {
+        Orbit::from_elements(
+            periapsis.get::<meter>(),
+            eccentricity,
+            inclination.get::<radian>(),
+            ascending_node.get::<radian>(),
+            angle_of_periapsis.get::<radian>())
+    }
+
+    /// `periapsis`, as a typed quantity
+    pub fn periapsis_quantity(&self) -> Length { Length::new::<meter>(self.periapsis()) }
+    /// None for parabolic orbits, negative for hyperbolic ones
+    pub fn semimajor_axis_quantity(&self) -> Option<Length> { self.semimajor_axis().map(Length::new::<meter>) }
+    /// `inclination`, as a typed quantity
+    pub fn inclination_quantity(&self) -> Angle { Angle::new::<radian>(self.inclination()) }
+    /// `ascending_node`, as a typed quantity
+    pub fn ascending_node_quantity(&self) -> Angle { Angle::new::<radian>(self.ascending_node()) }
+    /// `angle_of_periapsis`, as a typed quantity
+    pub fn angle_of_periapsis_quantity(&self) -> Angle { Angle::new::<radian>(self.angle_of_periapsis()) }
+    /// None for open orbits
+    pub fn orbital_period_quantity(&self, body : &Body) -> Option<Time> { self.orbital_period(body).map(Time::new::<second>) }
+
+    /// `vis_viva` at a typed distance from the focus
+    pub fn vis_viva_quantity(&self, body : &Body, radius : Length) -> Velocity {
+        Velocity::new::<meter_per_second>(self.vis_viva(body, radius.get::<meter>()))
+    }
+}
+
+impl StateVector {
+    /// Distance from the center of the body, as a typed quantity
+    pub fn radius_quantity(&self) -> Length { Length::new::<meter>(self.radius()) }
+    /// Magnitude of the velocity, as a typed quantity
+    pub fn speed_quantity(&self) -> Velocity { Velocity::new::<meter_per_second>(self.speed()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uom::si::angle::{ degree };
+    use uom::si::length::{ kilometer };
+    use uom::si::velocity::{ kilometer_per_second };
+
+    #[test]
+    fn quantities_convert_at_the_boundary() {
+        let body = Body::from_quantities(Mass::new::<kilogram>(5.972e24), Length::new::<kilometer>(6371.0), None, 0.0);
+        assert_eq!(body.radius, 6.371e6);
+        let orbit = Orbit::from_quantities(Length::new::<kilometer>(7000.0), 0.0, Angle::new::<degree>(90.0), Angle::new::<radian>(0.0), Angle::new::<radian>(0.0));
+        assert!((orbit.inclination() - std::f64::consts::FRAC_PI_2).abs() < 1e-15);
+        let speed = orbit.vis_viva_quantity(&body, orbit.periapsis_quantity());
+        assert!((speed.get::<kilometer_per_second>() - 7.546).abs() < 1e-3);
+    }
+}
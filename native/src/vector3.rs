@@ -0,0 +1,115 @@
+use std::ops::{ Add, Sub, Mul, Neg };
+
+/// Double precision vector for positions and velocities, meters and meters/second
+#[derive(Debug)]
+#[derive(PartialEq)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct Vector3 {
+    pub x : f64,
+    pub y : f64,
+    pub z : f64
+}
+
+impl Vector3 {
+    pub fn new(x : f64, y : f64, z : f64) -> Vector3 {
+        Vector3 { x, y, z }
+    }
+
+    pub fn zero() -> Vector3 { Vector3::new(0.0, 0.0, 0.0) }
+    pub fn i_hat() -> Vector3 { Vector3::new(1.0, 0.0, 0.0) }
+    pub fn j_hat() -> Vector3 { Vector3::new(0.0, 1.0, 0.0) }
+    pub fn k_hat() -> Vector3 { Vector3::new(0.0, 0.0, 1.0) }
+
+    pub fn magnitude_squared(&self) -> f64 {
+        self.dot(*self)
+    }
+
+    pub fn magnitude(&self) -> f64 {
+        self.magnitude_squared().sqrt()
+    }
+
+    pub fn dot(&self, v : Vector3) -> f64 {
+        self.x * v.x + self.y * v.y + self.z * v.z
+    }
+
+    pub fn cross(&self, v : Vector3) -> Vector3 {
+        Vector3 {
+            x : self.y * v.z - self.z * v.y,
+            y : self.z * v.x - self.x * v.z,
+            z : self.x * v.y - self.y * v.x
+        }
+    }
+
+    pub fn unit(&self) -> Vector3 {
+        self.scale(1.0 / self.magnitude())
+    }
+
+    pub fn scale(&self, s : f64) -> Vector3 {
+        Vector3 { x : self.x * s, y : self.y * s, z : self.z * s }
+    }
+
+    /// Right-handed rotation about the x axis, radians
+    pub fn rotate_x(&self, theta : f64) -> Vector3 {
+        let (s, c) = theta.sin_cos();
+        Vector3 { x : self.x, y : c * self.y - s * self.z, z : s * self.y + c * self.z }
+    }
+
+    /// Right-handed rotation about the z axis, radians
+    pub fn rotate_z(&self, theta : f64) -> Vector3 {
+        let (s, c) = theta.sin_cos();
+        Vector3 { x : c * self.x - s * self.y, y : s * self.x + c * self.y, z : self.z }
+    }
+}
+
+impl Add for Vector3 {
+    type Output = Vector3;
+    fn add(self, v : Vector3) -> Vector3 {
+        Vector3 { x : self.x + v.x, y : self.y + v.y, z : self.z + v.z }
+    }
+}
+
+impl Sub for Vector3 {
+    type Output = Vector3;
+    fn sub(self, v : Vector3) -> Vector3 {
+        Vector3 { x : self.x - v.x, y : self.y - v.y, z : self.z - v.z }
+    }
+}
+
+impl Mul<f64> for Vector3 {
+    type Output = Vector3;
+    fn mul(self, s : f64) -> Vector3 {
+        self.scale(s)
+    }
+}
+
+impl Mul<Vector3> for f64 {
+    type Output = Vector3;
+    fn mul(self, v : Vector3) -> Vector3 {
+        v.scale(self)
+    }
+}
+
+impl Neg for Vector3 {
+    type Output = Vector3;
+    fn neg(self) -> Vector3 {
+        self.scale(-1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Vector3;
+    use std::f64::consts::{ PI };
+
+    #[test]
+    fn cross_product() {
+        assert_eq!(Vector3::i_hat().cross(Vector3::j_hat()), Vector3::k_hat());
+    }
+
+    #[test]
+    fn rotate_z_quarter_turn() {
+        let rotated = Vector3::i_hat().rotate_z(PI / 2.0);
+        assert!((rotated - Vector3::j_hat()).magnitude() < 1e-12);
+    }
+}